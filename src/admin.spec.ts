@@ -0,0 +1,152 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  encodeCircuit,
+  listCircuits,
+  proposeCircuit,
+  ProposedCircuit
+} from './admin';
+import { Signer } from './crypto';
+import { TransportRequest, TransportResponse } from './transport';
+
+function ascii(value: string): number[] {
+  return value.split('').map(char => char.charCodeAt(0));
+}
+
+const circuit: ProposedCircuit = {
+  circuitId: 'AAAAA-BBBBB',
+  roster: [],
+  members: [],
+  authorizationType: 'Trust',
+  persistence: 'Any',
+  durability: 'NoDurability',
+  routes: 'Any',
+  managementType: 'mgmt',
+  applicationMetadata: ''
+};
+
+describe('listCircuits(options)', () => {
+  it('should request the circuits and convert them to camelCase', async () => {
+    const body = JSON.stringify({
+      data: [
+        {
+          id: 'AAAAA-BBBBB',
+          members: ['alpha', 'beta'],
+          roster: [
+            {
+              service_id: 'a000',
+              service_type: 'scabbard',
+              allowed_nodes: ['alpha'],
+              arguments: [['admin_keys', '["02ab"]']]
+            }
+          ],
+          management_type: 'mgmt'
+        }
+      ],
+      paging: { total: 1 }
+    });
+    const transport = jest.fn<Promise<TransportResponse>, [TransportRequest]>(
+      async () => ({ status: 200, headers: {}, body })
+    );
+
+    const page = await listCircuits({
+      splinterURL: 'http://splinterd:8085/',
+      managementType: 'mgmt',
+      member: 'alpha',
+      transport
+    });
+
+    expect(transport.mock.calls[0]).toMatchObject([
+      {
+        method: 'GET',
+        url:
+          'http://splinterd:8085/admin/circuits?management_type=mgmt&filter=alpha',
+        headers: { SplinterProtocolVersion: '1' }
+      }
+    ]);
+    expect(page.data).toEqual([
+      {
+        id: 'AAAAA-BBBBB',
+        members: ['alpha', 'beta'],
+        roster: [
+          {
+            serviceId: 'a000',
+            serviceType: 'scabbard',
+            allowedNodes: ['alpha'],
+            arguments: { admin_keys: '["02ab"]' }
+          }
+        ],
+        managementType: 'mgmt',
+        displayName: undefined,
+        circuitVersion: undefined,
+        circuitStatus: undefined
+      }
+    ]);
+  });
+});
+
+describe('encodeCircuit(circuit)', () => {
+  it('should serialize the circuit as a Circuit protobuf message', () => {
+    expect(Array.from(encodeCircuit(circuit))).toEqual([
+      0x0a,
+      11,
+      ...ascii('AAAAA-BBBBB'),
+      0x20,
+      1,
+      0x28,
+      1,
+      0x30,
+      1,
+      0x38,
+      1,
+      0x42,
+      4,
+      ...ascii('mgmt')
+    ]);
+  });
+});
+
+describe('proposeCircuit(circuit, options)', () => {
+  it('should submit a signed circuit management payload', async () => {
+    const transport = jest.fn<Promise<TransportResponse>, [TransportRequest]>(
+      async () => ({ status: 202, headers: {}, body: '' })
+    );
+    const signer: Signer = {
+      getPublicKey: () => '02'.padEnd(66, 'a'),
+      sign: jest.fn(async () => 'b'.repeat(128)),
+      verify: () => true
+    };
+
+    await proposeCircuit(circuit, {
+      splinterURL: 'http://splinterd:8085',
+      requesterNodeId: 'alpha',
+      signer,
+      transport
+    });
+
+    const request = transport.mock.calls[0][0];
+    const body = request.body as Uint8Array;
+    expect(request.url).toEqual('http://splinterd:8085/admin/submit');
+    expect(request.headers['Content-Type']).toEqual(
+      'application/octet-stream'
+    );
+    // The header starts with the CIRCUIT_CREATE_REQUEST action, and is the
+    // first field of the payload.
+    const header = (signer.sign as jest.Mock).mock.calls[0][0];
+    expect(Array.from(header.slice(0, 2))).toEqual([0x08, 1]);
+    expect(Array.from(body.slice(0, 2))).toEqual([0x0a, header.length]);
+  });
+});
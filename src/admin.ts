@@ -0,0 +1,535 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { assertAndGetWindowCanopy } from './canopy';
+import { canopySigner, sha512, Signer } from './crypto';
+import { hexToBytes } from './encoding';
+import { http, parseJSON, RequestOptions } from './http';
+import { ProtobufWriter } from './protobuf';
+
+export type AuthorizationType = 'Trust' | 'Challenge';
+export type Persistence = 'Any';
+export type Durability = 'NoDurability';
+export type RouteType = 'Any';
+export type VoteType = 'Accept' | 'Reject';
+
+export interface CircuitService {
+  serviceId: string;
+  serviceType: string;
+  allowedNodes: string[];
+  arguments: { [key: string]: string };
+}
+
+export interface Circuit {
+  id: string;
+  members: string[];
+  roster: CircuitService[];
+  managementType: string;
+  displayName?: string;
+  circuitVersion?: number;
+  circuitStatus?: string;
+}
+
+export interface CircuitMember {
+  nodeId: string;
+  endpoints: string[];
+  /** Hex encoded public key, used by challenge authorization. */
+  publicKey?: string;
+}
+
+export interface ProposedCircuit {
+  circuitId: string;
+  roster: CircuitService[];
+  members: CircuitMember[];
+  authorizationType: AuthorizationType;
+  persistence: Persistence;
+  durability: Durability;
+  routes: RouteType;
+  managementType: string;
+  /** Hex encoded application metadata. */
+  applicationMetadata: string;
+  comments?: string;
+  displayName?: string;
+  circuitVersion?: number;
+}
+
+export interface ProposalVote {
+  publicKey: string;
+  vote: VoteType;
+  voterNodeId: string;
+}
+
+export interface CircuitProposal {
+  proposalType: string;
+  circuitId: string;
+  circuitHash: string;
+  circuit: ProposedCircuit;
+  votes: ProposalVote[];
+  requester: string;
+  requesterNodeId: string;
+}
+
+export interface Paging {
+  current: string;
+  offset: number;
+  limit: number;
+  total: number;
+  first: string;
+  prev: string;
+  next: string;
+  last: string;
+}
+
+export interface Page<T> {
+  data: T[];
+  paging: Paging;
+}
+
+export interface AdminOptions extends RequestOptions {
+  /**
+   * Base URL of the Splinter node, defaulting to the `splinterURL` of the
+   * Canopy shared config.
+   */
+  splinterURL?: string;
+}
+
+export interface ListOptions extends AdminOptions {
+  /** Only return entries with this circuit management type. */
+  managementType?: string;
+  /** Only return entries with this node as a member. */
+  member?: string;
+  offset?: number;
+  limit?: number;
+}
+
+export interface SubmitOptions extends AdminOptions {
+  /** ID of the node the request is submitted on behalf of. */
+  requesterNodeId: string;
+  /**
+   * Signs the payload, defaulting to signing with the keys set in Canopy
+   * through its `sign` function.
+   */
+  signer?: Signer;
+}
+
+// Raw, snake_case representations returned by the Splinter REST API.
+interface RawService {
+  service_id: string;
+  service_type: string;
+  allowed_nodes: string[];
+  arguments: { [key: string]: string } | [string, string][];
+}
+
+interface RawCircuit {
+  id: string;
+  members: string[];
+  roster: RawService[];
+  management_type: string;
+  display_name?: string;
+  circuit_version?: number;
+  circuit_status?: string;
+}
+
+interface RawProposedCircuit {
+  circuit_id: string;
+  roster: RawService[];
+  members: { node_id: string; endpoints: string[]; public_key?: string }[];
+  authorization_type: AuthorizationType;
+  persistence: Persistence;
+  durability: Durability;
+  routes: RouteType;
+  circuit_management_type: string;
+  application_metadata: string;
+  comments?: string;
+  display_name?: string;
+  circuit_version?: number;
+}
+
+interface RawProposal {
+  proposal_type: string;
+  circuit_id: string;
+  circuit_hash: string;
+  circuit: RawProposedCircuit;
+  votes: { public_key: string; vote: VoteType; voter_node_id: string }[];
+  requester: string;
+  requester_node_id: string;
+}
+
+const SPLINTER_PROTOCOL_VERSION = '1';
+
+// Values of the CircuitManagementPayload.Action enum, which are also the
+// numbers of the fields holding each action in the payload.
+const CIRCUIT_CREATE_REQUEST = 1;
+const CIRCUIT_PROPOSAL_VOTE = 2;
+const CIRCUIT_DISBAND_REQUEST = 8;
+const CIRCUIT_PURGE_REQUEST = 9;
+const CIRCUIT_ABANDON = 10;
+
+const ACTION_FIELDS: { [action: number]: number } = {
+  [CIRCUIT_CREATE_REQUEST]: 3,
+  [CIRCUIT_PROPOSAL_VOTE]: 4,
+  [CIRCUIT_DISBAND_REQUEST]: 10,
+  [CIRCUIT_PURGE_REQUEST]: 11,
+  [CIRCUIT_ABANDON]: 12
+};
+
+const AUTHORIZATION_TYPES = { Trust: 1, Challenge: 2 };
+const PERSISTENCE_TYPES = { Any: 1 };
+const DURABILITY_TYPES = { NoDurability: 1 };
+const ROUTE_TYPES = { Any: 1 };
+const VOTES = { Accept: 1, Reject: 2 };
+
+function toService(raw: RawService): CircuitService {
+  const args = Array.isArray(raw.arguments)
+    ? raw.arguments.reduce(
+        (map: { [key: string]: string }, [key, value]) => ({
+          ...map,
+          [key]: value
+        }),
+        {}
+      )
+    : raw.arguments || {};
+  return {
+    serviceId: raw.service_id,
+    serviceType: raw.service_type,
+    allowedNodes: raw.allowed_nodes,
+    arguments: args
+  };
+}
+
+function toCircuit(raw: RawCircuit): Circuit {
+  return {
+    id: raw.id,
+    members: raw.members,
+    roster: raw.roster.map(toService),
+    managementType: raw.management_type,
+    displayName: raw.display_name,
+    circuitVersion: raw.circuit_version,
+    circuitStatus: raw.circuit_status
+  };
+}
+
+function toProposal(raw: RawProposal): CircuitProposal {
+  const { circuit } = raw;
+  return {
+    proposalType: raw.proposal_type,
+    circuitId: raw.circuit_id,
+    circuitHash: raw.circuit_hash,
+    circuit: {
+      circuitId: circuit.circuit_id,
+      roster: circuit.roster.map(toService),
+      members: circuit.members.map(member => ({
+        nodeId: member.node_id,
+        endpoints: member.endpoints,
+        publicKey: member.public_key
+      })),
+      authorizationType: circuit.authorization_type,
+      persistence: circuit.persistence,
+      durability: circuit.durability,
+      routes: circuit.routes,
+      managementType: circuit.circuit_management_type,
+      applicationMetadata: circuit.application_metadata,
+      comments: circuit.comments,
+      displayName: circuit.display_name,
+      circuitVersion: circuit.circuit_version
+    },
+    votes: raw.votes.map(vote => ({
+      publicKey: vote.public_key,
+      vote: vote.vote,
+      voterNodeId: vote.voter_node_id
+    })),
+    requester: raw.requester,
+    requesterNodeId: raw.requester_node_id
+  };
+}
+
+function adminURL(
+  path: string,
+  splinterURL: string | undefined,
+  query: { [name: string]: string | number | undefined } = {}
+): string {
+  const params = Object.keys(query)
+    .filter(name => query[name] !== undefined)
+    .map(name => `${name}=${encodeURIComponent(String(query[name]))}`);
+  const base = (
+    splinterURL ||
+    assertAndGetWindowCanopy().getSharedConfig().canopyConfig.splinterURL
+  ).replace(/\/+$/, '');
+  return params.length > 0
+    ? `${base}${path}?${params.join('&')}`
+    : `${base}${path}`;
+}
+
+async function getJSON<T>(url: string, options: AdminOptions): Promise<T> {
+  const response = await http('GET', url, null, {
+    ...options,
+    headers: { SplinterProtocolVersion: SPLINTER_PROTOCOL_VERSION }
+  });
+  return parseJSON('GET', url, response) as T;
+}
+
+/**
+ * Lists the circuits the node is a member of.
+ * @param {object}  options   Filters, paging and request options
+ */
+export async function listCircuits({
+  managementType,
+  member,
+  offset,
+  limit,
+  ...options
+}: ListOptions = {}): Promise<Page<Circuit>> {
+  const url = adminURL('/admin/circuits', options.splinterURL, {
+    management_type: managementType,
+    filter: member,
+    offset,
+    limit
+  });
+  const page = await getJSON<Page<RawCircuit>>(url, options);
+  return { data: page.data.map(toCircuit), paging: page.paging };
+}
+
+/**
+ * Fetches a single circuit.
+ * @param {string}  circuitId The ID of the circuit
+ * @param {object}  options   Request options
+ */
+export async function getCircuit(
+  circuitId: string,
+  options: AdminOptions = {}
+): Promise<Circuit> {
+  const url = adminURL(
+    `/admin/circuits/${encodeURIComponent(circuitId)}`,
+    options.splinterURL
+  );
+  return toCircuit(await getJSON<RawCircuit>(url, options));
+}
+
+/**
+ * Lists the circuit proposals pending on the node.
+ * @param {object}  options   Filters, paging and request options
+ */
+export async function listProposals({
+  managementType,
+  member,
+  offset,
+  limit,
+  ...options
+}: ListOptions = {}): Promise<Page<CircuitProposal>> {
+  const url = adminURL('/admin/proposals', options.splinterURL, {
+    management_type: managementType,
+    member,
+    offset,
+    limit
+  });
+  const page = await getJSON<Page<RawProposal>>(url, options);
+  return { data: page.data.map(toProposal), paging: page.paging };
+}
+
+/**
+ * Fetches the proposal for a circuit.
+ * @param {string}  circuitId The ID of the proposed circuit
+ * @param {object}  options   Request options
+ */
+export async function getProposal(
+  circuitId: string,
+  options: AdminOptions = {}
+): Promise<CircuitProposal> {
+  const url = adminURL(
+    `/admin/proposals/${encodeURIComponent(circuitId)}`,
+    options.splinterURL
+  );
+  return toProposal(await getJSON<RawProposal>(url, options));
+}
+
+function encodeService(service: CircuitService): Uint8Array {
+  const args = Object.keys(service.arguments).map(key =>
+    new ProtobufWriter()
+      .string(1, key)
+      .string(2, service.arguments[key])
+      .finish()
+  );
+  return new ProtobufWriter()
+    .string(1, service.serviceId)
+    .string(2, service.serviceType)
+    .repeatedString(3, service.allowedNodes)
+    .repeatedMessage(4, args)
+    .finish();
+}
+
+function encodeMember(member: CircuitMember): Uint8Array {
+  return new ProtobufWriter()
+    .string(1, member.nodeId)
+    .repeatedString(2, member.endpoints)
+    .bytes(3, member.publicKey ? hexToBytes(member.publicKey) : undefined)
+    .finish();
+}
+
+/**
+ * Serializes a circuit definition as a `Circuit` protobuf message.
+ * @param {object}  circuit   The proposed circuit
+ */
+export function encodeCircuit(circuit: ProposedCircuit): Uint8Array {
+  return new ProtobufWriter()
+    .string(1, circuit.circuitId)
+    .repeatedMessage(2, circuit.roster.map(encodeService))
+    .repeatedMessage(3, circuit.members.map(encodeMember))
+    .uint(4, AUTHORIZATION_TYPES[circuit.authorizationType])
+    .uint(5, PERSISTENCE_TYPES[circuit.persistence])
+    .uint(6, DURABILITY_TYPES[circuit.durability])
+    .uint(7, ROUTE_TYPES[circuit.routes])
+    .string(8, circuit.managementType)
+    .bytes(9, hexToBytes(circuit.applicationMetadata))
+    .string(10, circuit.comments)
+    .string(11, circuit.displayName)
+    .uint(12, circuit.circuitVersion || 0)
+    .finish();
+}
+
+/**
+ * Serializes a `CircuitCreateRequest` protobuf message for a circuit.
+ * @param {object}  circuit   The proposed circuit
+ */
+export function encodeCircuitCreateRequest(
+  circuit: ProposedCircuit
+): Uint8Array {
+  return new ProtobufWriter().message(1, encodeCircuit(circuit)).finish();
+}
+
+function circuitIdRequest(circuitId: string): Uint8Array {
+  return new ProtobufWriter().string(1, circuitId).finish();
+}
+
+/**
+ * Signs and serializes a `CircuitManagementPayload` holding the given action.
+ */
+async function buildPayload(
+  action: number,
+  actionBytes: Uint8Array,
+  requesterNodeId: string,
+  signer: Signer
+): Promise<Uint8Array> {
+  const header = new ProtobufWriter()
+    .uint(1, action)
+    .bytes(2, hexToBytes(signer.getPublicKey()))
+    .bytes(3, hexToBytes(sha512(actionBytes)))
+    .string(4, requesterNodeId)
+    .finish();
+  const signature = await signer.sign(header);
+  return new ProtobufWriter()
+    .bytes(1, header)
+    .bytes(2, hexToBytes(signature))
+    .message(ACTION_FIELDS[action], actionBytes)
+    .finish();
+}
+
+async function submitPayload(
+  action: number,
+  actionBytes: Uint8Array,
+  { requesterNodeId, signer = canopySigner(), ...options }: SubmitOptions
+): Promise<void> {
+  const payload = await buildPayload(
+    action,
+    actionBytes,
+    requesterNodeId,
+    signer
+  );
+  await http('POST', adminURL('/admin/submit', options.splinterURL), payload, {
+    ...options,
+    headers: {
+      'Content-Type': 'application/octet-stream',
+      SplinterProtocolVersion: SPLINTER_PROTOCOL_VERSION
+    }
+  });
+}
+
+/**
+ * Proposes a new circuit.
+ * @param {object}  circuit   The circuit to propose
+ * @param {object}  options   Requester, signer and request options
+ */
+export async function proposeCircuit(
+  circuit: ProposedCircuit,
+  options: SubmitOptions
+): Promise<void> {
+  return submitPayload(
+    CIRCUIT_CREATE_REQUEST,
+    encodeCircuitCreateRequest(circuit),
+    options
+  );
+}
+
+/**
+ * Votes on a circuit proposal.
+ * @param {object}  proposal  The proposal, or its circuit ID and hash
+ * @param {string}  vote      'Accept' or 'Reject'
+ * @param {object}  options   Requester, signer and request options
+ */
+export async function voteOnProposal(
+  { circuitId, circuitHash }: { circuitId: string; circuitHash: string },
+  vote: VoteType,
+  options: SubmitOptions
+): Promise<void> {
+  const request = new ProtobufWriter()
+    .string(1, circuitId)
+    .string(2, circuitHash)
+    .uint(3, VOTES[vote])
+    .finish();
+  return submitPayload(CIRCUIT_PROPOSAL_VOTE, request, options);
+}
+
+/**
+ * Proposes disbanding an active circuit.
+ * @param {string}  circuitId The ID of the circuit
+ * @param {object}  options   Requester, signer and request options
+ */
+export async function disbandCircuit(
+  circuitId: string,
+  options: SubmitOptions
+): Promise<void> {
+  return submitPayload(
+    CIRCUIT_DISBAND_REQUEST,
+    circuitIdRequest(circuitId),
+    options
+  );
+}
+
+/**
+ * Removes a disbanded or abandoned circuit, and its state, from the node.
+ * @param {string}  circuitId The ID of the circuit
+ * @param {object}  options   Requester, signer and request options
+ */
+export async function purgeCircuit(
+  circuitId: string,
+  options: SubmitOptions
+): Promise<void> {
+  return submitPayload(
+    CIRCUIT_PURGE_REQUEST,
+    circuitIdRequest(circuitId),
+    options
+  );
+}
+
+/**
+ * Leaves an active circuit without the agreement of the other members.
+ * @param {string}  circuitId The ID of the circuit
+ * @param {object}  options   Requester, signer and request options
+ */
+export async function abandonCircuit(
+  circuitId: string,
+  options: SubmitOptions
+): Promise<void> {
+  return submitPayload(CIRCUIT_ABANDON, circuitIdRequest(circuitId), options);
+}
@@ -0,0 +1,45 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { assertAndGetWindowCanopy } from './canopy';
+
+/**
+ * Supplies the token sent as the bearer token of authenticated requests.
+ */
+export interface TokenProvider {
+  (): string | undefined | Promise<string | undefined>;
+}
+
+/**
+ * Reads the token of the user currently logged in to Canopy.
+ */
+export const canopyTokenProvider: TokenProvider = () => {
+  const user = assertAndGetWindowCanopy().getUser();
+  return user ? user.token : undefined;
+};
+
+/**
+ * Resolves the token to send with a request.
+ * @param auth - `true` to use the Canopy user's token, or a custom provider.
+ */
+export async function resolveToken(
+  auth?: boolean | TokenProvider
+): Promise<string | undefined> {
+  if (!auth) {
+    return undefined;
+  }
+  const provider = auth === true ? canopyTokenProvider : auth;
+  return provider();
+}
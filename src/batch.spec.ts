@@ -0,0 +1,118 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  buildBatchList,
+  contractAddress,
+  sabreTransaction,
+  TransactionParams
+} from './batch';
+import { publicKeyFromPrivate, sha512, Signer } from './crypto';
+import { bytesToHex, utf8Encode } from './encoding';
+import { installMockCanopy } from './testing';
+
+// A signer whose signatures are numbered, so that IDs are predictable.
+function countingSigner(): Signer & { messages: Uint8Array[] } {
+  const messages: Uint8Array[] = [];
+  return {
+    messages,
+    getPublicKey: () => '02'.padEnd(66, 'a'),
+    sign: async (message: Uint8Array) => {
+      messages.push(message);
+      return `${messages.length}`.padStart(128, '0');
+    },
+    verify: () => true
+  };
+}
+
+function contains(haystack: Uint8Array, needle: Uint8Array): boolean {
+  return bytesToHex(haystack).includes(bytesToHex(needle));
+}
+
+const transaction: TransactionParams = {
+  familyName: 'intkey',
+  familyVersion: '1.0',
+  inputs: ['1cf126'],
+  outputs: ['1cf126'],
+  payload: new Uint8Array([1, 2, 3]),
+  nonce: 'nonce'
+};
+
+describe('buildBatchList(batches, options)', () => {
+  it('should sign each transaction and batch', async () => {
+    const signer = countingSigner();
+    const result = await buildBatchList([[transaction, transaction]], {
+      signer
+    });
+
+    // Both transactions are signed before the batch header.
+    expect(result.transactionIds).toEqual([
+      '1'.padStart(128, '0'),
+      '2'.padStart(128, '0')
+    ]);
+    expect(result.batchIds).toEqual(['3'.padStart(128, '0')]);
+    expect(signer.messages).toHaveLength(3);
+  });
+
+  it('should include the payload hash in the transaction header', async () => {
+    const signer = countingSigner();
+    await buildBatchList([[transaction]], { signer });
+    const header = signer.messages[0];
+    expect(contains(header, utf8Encode(sha512(transaction.payload)))).toBe(
+      true
+    );
+    expect(contains(header, utf8Encode('intkey'))).toBe(true);
+  });
+
+  it('should build one batch per list of transactions', async () => {
+    const result = await buildBatchList([[transaction], [transaction]], {
+      signer: countingSigner()
+    });
+    expect(result.batchIds).toHaveLength(2);
+    expect(result.transactionIds).toHaveLength(2);
+    expect(result.batchList[0]).toEqual(0x0a);
+  });
+  it('should sign with the Canopy keys without reading them', async () => {
+    const privateKey = 'a'.repeat(64);
+    const publicKey = publicKeyFromPrivate(privateKey);
+    const canopy = installMockCanopy({ keys: { privateKey, publicKey } });
+    const sign = jest.spyOn(canopy, 'sign');
+    const getKeys = jest.spyOn(canopy, 'getKeys');
+    const result = await buildBatchList([[transaction]]);
+    expect(sign).toHaveBeenCalledTimes(2);
+    expect(getKeys).not.toHaveBeenCalled();
+    expect(contains(result.batchList, utf8Encode(publicKey))).toBe(true);
+    canopy.uninstall();
+  });
+});
+
+describe('sabreTransaction(params)', () => {
+  it('should add the Sabre registry addresses to the inputs and outputs', () => {
+    const params = sabreTransaction({
+      contractName: 'xo',
+      contractVersion: '0.1',
+      inputs: ['5b7349aaaa'],
+      outputs: ['5b7349aaaa'],
+      payload: new Uint8Array([1])
+    });
+    expect(params.familyName).toEqual('sabre');
+    expect(params.inputs).toHaveLength(4);
+    expect(params.inputs).toContain(contractAddress('xo', '0.1'));
+    expect(params.inputs[params.inputs.length - 1]).toEqual('5b7349aaaa');
+    params.inputs.slice(0, 3).forEach(address => {
+      expect(address).toMatch(/^00ec0[0-2][0-9a-f]{64}$/);
+    });
+  });
+});
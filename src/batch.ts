@@ -0,0 +1,241 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { canopySigner, sha512, Signer } from './crypto';
+import { bytesToHex, utf8Encode } from './encoding';
+import { ProtobufWriter } from './protobuf';
+
+export interface TransactionParams {
+  familyName: string;
+  familyVersion: string;
+  /** State addresses the transaction reads from. */
+  inputs: string[];
+  /** State addresses the transaction writes to. */
+  outputs: string[];
+  payload: Uint8Array;
+  /** IDs of transactions that must be committed before this one. */
+  dependencies?: string[];
+  /** Defaults to a random value, making the transaction ID unique. */
+  nonce?: string;
+}
+
+export interface SabreTransactionParams {
+  contractName: string;
+  contractVersion: string;
+  inputs: string[];
+  outputs: string[];
+  /** The payload passed to the smart contract. */
+  payload: Uint8Array;
+  dependencies?: string[];
+  nonce?: string;
+  /** Version of the Sabre transaction family, defaulting to 0.5. */
+  sabreVersion?: string;
+}
+
+export interface BatchOptions {
+  /**
+   * Signs the transactions and batches, defaulting to signing with the keys
+   * set in Canopy through its `sign` function.
+   */
+  signer?: Signer;
+}
+
+export interface BuiltBatchList {
+  /** The serialized batch list, ready for `submitBatchList`. */
+  batchList: Uint8Array;
+  batchIds: string[];
+  transactionIds: string[];
+}
+
+interface BuiltTransaction {
+  id: string;
+  bytes: Uint8Array;
+}
+
+const SABRE_FAMILY_NAME = 'sabre';
+const SABRE_FAMILY_VERSION = '0.5';
+const SABRE_EXECUTE_CONTRACT = 3;
+
+const NAMESPACE_REGISTRY_PREFIX = '00ec00';
+const CONTRACT_REGISTRY_PREFIX = '00ec01';
+const CONTRACT_PREFIX = '00ec02';
+
+function defaultNonce(): string {
+  const bytes = new Uint8Array(16);
+  if (typeof crypto !== 'undefined' && crypto.getRandomValues) {
+    crypto.getRandomValues(bytes);
+  } else {
+    // The nonce only needs to be unique, not unpredictable.
+    for (let i = 0; i < bytes.length; i += 1) {
+      bytes[i] = Math.floor(Math.random() * 256);
+    }
+  }
+  return bytesToHex(bytes);
+}
+
+function hashAddress(prefix: string, value: string): string {
+  return prefix + sha512(utf8Encode(value)).slice(0, 64);
+}
+
+/**
+ * Computes the address of a Sabre namespace registry, which is keyed by the
+ * first six characters of the addresses in the namespace.
+ */
+export function namespaceRegistryAddress(address: string): string {
+  return hashAddress(NAMESPACE_REGISTRY_PREFIX, address.slice(0, 6));
+}
+
+export function contractRegistryAddress(contractName: string): string {
+  return hashAddress(CONTRACT_REGISTRY_PREFIX, contractName);
+}
+
+export function contractAddress(
+  contractName: string,
+  contractVersion: string
+): string {
+  return hashAddress(CONTRACT_PREFIX, `${contractName},${contractVersion}`);
+}
+
+function unique(values: string[]): string[] {
+  return values.filter((value, index) => values.indexOf(value) === index);
+}
+
+/**
+ * Wraps a smart contract payload in a Sabre transaction, adding the contract
+ * and namespace registry addresses Sabre needs to the inputs and outputs.
+ */
+export function sabreTransaction({
+  contractName,
+  contractVersion,
+  inputs,
+  outputs,
+  payload,
+  dependencies,
+  nonce,
+  sabreVersion = SABRE_FAMILY_VERSION
+}: SabreTransactionParams): TransactionParams {
+  const execute = new ProtobufWriter()
+    .string(1, contractName)
+    .string(2, contractVersion)
+    .repeatedString(3, inputs)
+    .repeatedString(4, outputs)
+    .bytes(5, payload)
+    .finish();
+  const sabrePayload = new ProtobufWriter()
+    .uint(1, SABRE_EXECUTE_CONTRACT)
+    .message(4, execute)
+    .finish();
+
+  const contractAddresses = [
+    contractRegistryAddress(contractName),
+    contractAddress(contractName, contractVersion)
+  ];
+  return {
+    familyName: SABRE_FAMILY_NAME,
+    familyVersion: sabreVersion,
+    inputs: unique([
+      ...contractAddresses,
+      ...inputs.map(namespaceRegistryAddress),
+      ...inputs
+    ]),
+    outputs: unique([
+      ...contractAddresses,
+      ...outputs.map(namespaceRegistryAddress),
+      ...outputs
+    ]),
+    payload: sabrePayload,
+    dependencies,
+    nonce
+  };
+}
+
+async function buildTransaction(
+  {
+    familyName,
+    familyVersion,
+    inputs,
+    outputs,
+    payload,
+    dependencies = [],
+    nonce = defaultNonce()
+  }: TransactionParams,
+  signer: Signer
+): Promise<BuiltTransaction> {
+  const publicKey = signer.getPublicKey();
+  const header = new ProtobufWriter()
+    .string(1, publicKey)
+    .repeatedString(2, dependencies)
+    .string(3, familyName)
+    .string(4, familyVersion)
+    .repeatedString(5, inputs)
+    .string(6, nonce)
+    .repeatedString(7, outputs)
+    .string(9, sha512(payload))
+    .string(10, publicKey)
+    .finish();
+  const id = await signer.sign(header);
+  const bytes = new ProtobufWriter()
+    .bytes(1, header)
+    .string(2, id)
+    .bytes(3, payload)
+    .finish();
+  return { id, bytes };
+}
+
+async function buildBatch(
+  transactions: TransactionParams[],
+  signer: Signer
+): Promise<{ id: string; bytes: Uint8Array; transactionIds: string[] }> {
+  const built = await Promise.all(
+    transactions.map(transaction => buildTransaction(transaction, signer))
+  );
+  const transactionIds = built.map(transaction => transaction.id);
+  const header = new ProtobufWriter()
+    .string(1, signer.getPublicKey())
+    .repeatedString(2, transactionIds)
+    .finish();
+  const id = await signer.sign(header);
+  const bytes = new ProtobufWriter()
+    .bytes(1, header)
+    .string(2, id)
+    .repeatedMessage(3, built.map(transaction => transaction.bytes))
+    .finish();
+  return { id, bytes, transactionIds };
+}
+
+/**
+ * Builds and signs a batch list, with one batch for each list of transactions.
+ * The transactions of a batch are committed or rejected together.
+ * @param {object[][]}  batches   The transactions of each batch
+ * @param {object}      options   The signer to use
+ */
+export async function buildBatchList(
+  batches: TransactionParams[][],
+  { signer = canopySigner() }: BatchOptions = {}
+): Promise<BuiltBatchList> {
+  const built = await Promise.all(
+    batches.map(transactions => buildBatch(transactions, signer))
+  );
+  return {
+    batchList: new ProtobufWriter()
+      .repeatedMessage(1, built.map(batch => batch.bytes))
+      .finish(),
+    batchIds: built.map(batch => batch.id),
+    transactionIds: built.reduce(
+      (ids: string[], batch) => ids.concat(batch.transactionIds),
+      []
+    )
+  };
+}
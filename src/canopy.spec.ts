@@ -0,0 +1,42 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+describe('outside of a Canopy', () => {
+  it('should import SaplingJS without window.$CANOPY in scope', async () => {
+    const saplingjs = await import('./index');
+    expect(saplingjs.isInCanopy()).toBe(false);
+    expect(typeof saplingjs.encryptKey).toEqual('function');
+  });
+
+  it('should raise a CanopyUnavailableError when a Canopy function is called', async () => {
+    const { getUser, CanopyUnavailableError } = await import('./index');
+    expect(() => getUser()).toThrow(CanopyUnavailableError);
+  });
+
+  it('should bind to window.$CANOPY once it is in scope', async () => {
+    const { getUser, isInCanopy } = await import('./index');
+    const user = { userId: 'late' };
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    (window as any).$CANOPY = { getUser: (): object => user };
+    try {
+      expect(isInCanopy()).toBe(true);
+      expect(getUser()).toBe(user);
+    } finally {
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      delete (window as any).$CANOPY;
+    }
+  });
+});
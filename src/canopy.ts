@@ -0,0 +1,363 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { CanopyUnavailableError } from './errors';
+import { Unsubscribe } from './events';
+import {
+  Publish,
+  Reply,
+  Request,
+  SaplingMessaging,
+  Subscribe
+} from './messaging';
+import {
+  DismissNotification,
+  GetNotifications,
+  MarkNotificationRead,
+  Notify,
+  OnNotificationsChange
+} from './notifications';
+import { SaplingRouter } from './routing';
+
+export interface User {
+  userId: string;
+  displayName?: string;
+  token?: string;
+}
+
+export interface KeyPair {
+  publicKey: string;
+  privateKey: string;
+}
+
+/**
+ * Sets the user logged in to Canopy, or `null` when the user logs out.
+ */
+export interface SetUser {
+  (user: User | null): void;
+}
+
+/**
+ * Sets the keys of the user logged in to Canopy, or `null` to clear them.
+ */
+export interface SetKeys {
+  (keys: KeyPair | null): void;
+}
+
+/**
+ * Called with the new user each time `setUser` is called, or with `null`
+ * when the user logs out.
+ */
+export interface UserChangeListener {
+  (user: User | null): void;
+}
+
+/**
+ * Called with the new keys each time `setKeys` is called, or with `null`
+ * when the keys are cleared.
+ */
+export interface KeysChangeListener {
+  (keys: KeyPair | null): void;
+}
+
+export interface OnUserChange {
+  (callback: UserChangeListener): Unsubscribe;
+}
+
+export interface OnKeysChange {
+  (callback: KeysChangeListener): Unsubscribe;
+}
+
+export interface SharedConfig {
+  canopyConfig: {
+    splinterURL: string;
+  };
+}
+
+export interface GetSharedConfig {
+  (): SharedConfig;
+}
+
+export interface GetUser {
+  (): User;
+}
+
+export interface GetKeys {
+  (): KeyPair;
+}
+
+/**
+ * Passed to an app's bootstrap function along with its DOM node. Handlers
+ * subscribed through the context only receive messages while the app is
+ * mounted.
+ */
+export interface SaplingContext extends SaplingMessaging {
+  /** The name the app was registered with. */
+  name: string;
+  /** Reads and changes the app's route. */
+  router: SaplingRouter;
+  /**
+   * The Canopy API, limited to the capabilities the app was registered with.
+   * Calls needing any other capability throw a `PermissionDeniedError`.
+   */
+  // eslint-disable-next-line no-use-before-define, @typescript-eslint/no-use-before-define
+  canopy: Canopy;
+}
+
+/**
+ * Access to the user's keys and data a sapling can be granted:
+ * - `keys:sign` signs payloads with the user's key, without revealing it
+ * - `keys:read` reads the user's key pair, including the private key
+ * - `keys:write` replaces the user's keys with `setKeys` or `setKeySigner`
+ * - `user:read` reads the user logged in to Canopy
+ * - `user:write` replaces the user with `setUser`
+ * - `config:read` reads the shared config
+ * - `apps:register` registers apps and config saplings, which can only be
+ *   given capabilities the registering sapling holds itself
+ */
+export type Capability =
+  | 'keys:sign'
+  | 'keys:read'
+  | 'keys:write'
+  | 'user:read'
+  | 'user:write'
+  | 'config:read'
+  | 'apps:register';
+
+export interface AppOptions {
+  /** Name other saplings use to refer to the app, such as in `linkTo`. */
+  name?: string;
+  /**
+   * Path the app is mounted at, such as `/circuits`. Defaults to the name of
+   * the app.
+   */
+  routePrefix?: string;
+  /**
+   * Capabilities the app needs, each of which must be listed by the app's
+   * manifest. Defaults to none.
+   */
+  capabilities?: Capability[];
+}
+
+/**
+ * Hooks Canopy calls as an app is navigated away from or hidden.
+ */
+export interface SaplingLifecycle {
+  /**
+   * Called when the app is unmounted, to release its timers, sockets and
+   * rendered components.
+   */
+  unmount?(): void;
+  /** Called when the app is hidden but kept mounted. */
+  suspend?(): void;
+  /** Called when a suspended app is shown again. */
+  resume?(): void;
+}
+
+/**
+ * Called when the app is unmounted.
+ */
+export interface Teardown {
+  (): void;
+}
+
+/**
+ * Renders an app into its DOM node. May return a teardown function, or
+ * lifecycle hooks, which Canopy calls when the app is unmounted.
+ */
+export interface AppBootstrap {
+  (domNode: Node, context: SaplingContext): void | Teardown | SaplingLifecycle;
+}
+
+export interface RegisterApp {
+  (bootstrapFunction: AppBootstrap, options?: AppOptions): void;
+}
+
+/**
+ * A concern handled by config saplings, such as login or theming. `Input` is
+ * the type of the value a config sapling is bootstrapped with, and `Output`
+ * the type of the value it must resolve with.
+ *
+ * The `input` and `output` fields are never set; they only carry the types.
+ */
+export interface ConfigNamespace<Input, Output> {
+  readonly name: string;
+  /** Namespaces are run in ascending order. */
+  readonly order: number;
+  readonly input?: Input;
+  readonly output?: Output;
+  /**
+   * Capabilities `apply` needs. Only saplings holding them can register config
+   * saplings for the namespace, and `apply` is given a Canopy limited to them.
+   */
+  readonly capabilities?: Capability[];
+  /**
+   * Applies the value a config sapling resolved with, such as storing the
+   * user a login sapling resolved with.
+   */
+  // eslint-disable-next-line no-use-before-define, @typescript-eslint/no-use-before-define
+  apply?(output: Output, canopy: Canopy): void;
+}
+
+/**
+ * Runs a config sapling with the input of its namespace, and a Canopy limited
+ * to the capabilities of the namespace. Calls made through that Canopy are
+ * never attributed to the mounted app, so it suits work the sapling does
+ * later, such as locking keys when a timer fires.
+ */
+export interface ConfigSaplingBootstrap<Input, Output> {
+  // eslint-disable-next-line no-use-before-define, @typescript-eslint/no-use-before-define
+  (input: Input, canopy: Canopy): Output | Promise<Output>;
+}
+
+export type BuiltInConfigNamespace = 'login' | 'notifications';
+
+export interface RegisterConfigSapling {
+  <Input, Output>(
+    configNamespace: ConfigNamespace<Input, Output>,
+    bootstrapFunction: ConfigSaplingBootstrap<Input, Output>
+  ): void;
+  (
+    configNamespace: BuiltInConfigNamespace,
+    // eslint-disable-next-line no-use-before-define, @typescript-eslint/no-use-before-define
+    bootstrapFunction: (input: unknown, canopy: Canopy) => void
+  ): void;
+}
+
+/**
+ * Signs a message with the user's private key, returning the compact hex
+ * signature of its SHA-256 digest.
+ */
+export interface Sign {
+  (message: Uint8Array): Promise<string>;
+}
+
+/**
+ * Returns the public key of the keys set in Canopy.
+ */
+export interface GetPublicKey {
+  (): string;
+}
+
+/**
+ * Holds a private key outside of Canopy, such as a key session, and signs
+ * with it on Canopy's behalf.
+ */
+export interface KeySigner {
+  getPublicKey(): string;
+  sign(message: Uint8Array): Promise<string>;
+}
+
+/**
+ * Serves `sign` and `getPublicKey` with the given signer, clearing the keys
+ * set with `setKeys`. Passing `null` locks the keys: `sign` and
+ * `getPublicKey` then throw a `KeysLockedError` until new keys or a new
+ * signer are set.
+ */
+export interface SetKeySigner {
+  (signer: KeySigner | null): void;
+}
+
+export interface HideCanopy {
+  (): void;
+}
+
+export interface Canopy {
+  registerApp: RegisterApp;
+  registerConfigSapling: RegisterConfigSapling;
+  getUser: GetUser;
+  setUser: SetUser;
+  setKeys: SetKeys;
+  getKeys: GetKeys;
+  sign: Sign;
+  getPublicKey: GetPublicKey;
+  setKeySigner: SetKeySigner;
+  getSharedConfig: GetSharedConfig;
+  hideCanopy: HideCanopy;
+  onUserChange: OnUserChange;
+  onKeysChange: OnKeysChange;
+  publish: Publish;
+  subscribe: Subscribe;
+  request: Request;
+  reply: Reply;
+  notify: Notify;
+  getNotifications: GetNotifications;
+  markNotificationRead: MarkNotificationRead;
+  dismissNotification: DismissNotification;
+  onNotificationsChange: OnNotificationsChange;
+}
+
+/**
+ * Returns true if SaplingJS is running inside a Canopy, with `window.$CANOPY`
+ * in scope.
+ */
+export function isInCanopy(): boolean {
+  // In order to prevent the need to overwrite the window interface,
+  // a intentional `any` is cast here.
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  return typeof window !== 'undefined' && !!(window as any).$CANOPY;
+}
+
+export function assertAndGetWindowCanopy(): Canopy {
+  if (!isInCanopy()) {
+    throw new CanopyUnavailableError(
+      `Must be in a Canopy with 'window.$CANOPY' in scope to call this CanopyJS functions`
+    );
+  }
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  return (window as any).$CANOPY;
+}
+
+/**
+ * Returns a function that forwards its calls to the named Canopy function.
+ * The Canopy is looked up on every call rather than when SaplingJS is
+ * imported, so that SaplingJS can be imported outside of a Canopy.
+ */
+function bindCanopyFunction<K extends keyof Canopy>(name: K): Canopy[K] {
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  const forward = (...args: any[]): any => {
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    const fn = assertAndGetWindowCanopy()[name] as (...params: any[]) => any;
+    return fn(...args);
+  };
+  return forward as Canopy[K];
+}
+
+export const registerApp = bindCanopyFunction('registerApp');
+export const registerConfigSapling = bindCanopyFunction(
+  'registerConfigSapling'
+);
+export const getUser = bindCanopyFunction('getUser');
+export const setUser = bindCanopyFunction('setUser');
+export const setKeys = bindCanopyFunction('setKeys');
+export const getKeys = bindCanopyFunction('getKeys');
+export const sign = bindCanopyFunction('sign');
+export const getPublicKey = bindCanopyFunction('getPublicKey');
+export const setKeySigner = bindCanopyFunction('setKeySigner');
+export const getSharedConfig = bindCanopyFunction('getSharedConfig');
+export const hideCanopy = bindCanopyFunction('hideCanopy');
+export const onUserChange = bindCanopyFunction('onUserChange');
+export const onKeysChange = bindCanopyFunction('onKeysChange');
+export const publish = bindCanopyFunction('publish');
+export const subscribe = bindCanopyFunction('subscribe');
+export const request = bindCanopyFunction('request');
+export const reply = bindCanopyFunction('reply');
+export const notify = bindCanopyFunction('notify');
+export const getNotifications = bindCanopyFunction('getNotifications');
+export const markNotificationRead = bindCanopyFunction('markNotificationRead');
+export const dismissNotification = bindCanopyFunction('dismissNotification');
+export const onNotificationsChange = bindCanopyFunction(
+  'onNotificationsChange'
+);
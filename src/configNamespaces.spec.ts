@@ -0,0 +1,110 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { installMockCanopy, MockCanopy } from './testing';
+import { getKeys, getUser, registerConfigSapling } from './canopy';
+import {
+  defineConfigNamespace,
+  LOGIN_NAMESPACE,
+  resolveConfigNamespace
+} from './configNamespaces';
+import { PermissionDeniedError } from './errors';
+
+interface ThemeRequest {
+  preferDark: boolean;
+}
+
+const themeNamespace = defineConfigNamespace<ThemeRequest, string>('theme', {
+  order: 30
+});
+const localeNamespace = defineConfigNamespace<void, string>('locale');
+
+describe('config namespaces', () => {
+  let canopy: MockCanopy;
+
+  beforeEach(() => {
+    canopy = installMockCanopy({
+      manifests: [
+        {
+          name: 'login',
+          configNamespaces: ['login'],
+          capabilities: ['user:write', 'keys:write']
+        }
+      ]
+    });
+  });
+
+  afterEach(() => {
+    canopy.uninstall();
+  });
+
+  it('should resolve built-in namespaces by name', () => {
+    expect(resolveConfigNamespace('login')).toBe(LOGIN_NAMESPACE);
+    expect(resolveConfigNamespace(themeNamespace)).toBe(themeNamespace);
+  });
+
+  it('should run config saplings in namespace order', async () => {
+    const calls: string[] = [];
+    registerConfigSapling(localeNamespace, () => {
+      calls.push('locale');
+      return 'en-US';
+    });
+    registerConfigSapling(themeNamespace, ({ preferDark }) => {
+      calls.push('theme');
+      return preferDark ? 'dark' : 'light';
+    });
+    registerConfigSapling('notifications', () => {
+      calls.push('notifications');
+    });
+    registerConfigSapling('login', () => {
+      calls.push('login');
+    });
+    const results = await canopy.runConfigSaplings({
+      theme: { preferDark: true }
+    });
+    expect(calls).toEqual(['login', 'notifications', 'theme', 'locale']);
+    expect(results.map(({ output }) => output)).toEqual([
+      undefined,
+      undefined,
+      'dark',
+      'en-US'
+    ]);
+  });
+
+  it('should store the user and keys a login sapling resolves with', async () => {
+    const user = { userId: 'alice' };
+    const keys = { publicKey: '02ab', privateKey: 'cd' };
+    registerConfigSapling(LOGIN_NAMESPACE, async () => ({ user, keys }));
+    await canopy.runConfigSaplings();
+    expect(getUser()).toEqual(user);
+    expect(getKeys()).toEqual(keys);
+  });
+
+  it('should only register config saplings approved by a manifest', () => {
+    const privileged = defineConfigNamespace<void, void>('privileged', {
+      capabilities: ['keys:read']
+    });
+    expect(() => registerConfigSapling(privileged, jest.fn())).toThrow(
+      PermissionDeniedError
+    );
+    expect(canopy.configSaplings).toHaveLength(0);
+  });
+
+  it('should ignore the output of saplings registered by name', async () => {
+    registerConfigSapling('login', () => 'not a login result');
+    await canopy.runConfigSaplings();
+    expect(getUser()).toBeUndefined();
+  });
+});
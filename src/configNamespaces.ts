@@ -0,0 +1,114 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  BuiltInConfigNamespace,
+  Canopy,
+  Capability,
+  ConfigNamespace,
+  KeyPair,
+  User
+} from './canopy';
+
+/**
+ * Order of namespaces defined without one, after the built-in namespaces.
+ */
+export const DEFAULT_CONFIG_NAMESPACE_ORDER = 100;
+
+export interface ConfigNamespaceOptions<Output> {
+  /** Namespaces are run in ascending order. */
+  order?: number;
+  /** Capabilities `apply` needs. Defaults to none. */
+  capabilities?: Capability[];
+  /** Applies the value a config sapling of the namespace resolved with. */
+  apply?(output: Output, canopy: Canopy): void;
+}
+
+/**
+ * Declares a config namespace with a typed contract. Share the returned
+ * declaration between the Canopy app and the config saplings it runs.
+ *
+ * @example
+ * const themeNamespace = defineConfigNamespace<ThemeRequest, Theme>('theme', {
+ *   order: 30
+ * });
+ * registerConfigSapling(themeNamespace, async request => loadTheme(request));
+ */
+export function defineConfigNamespace<Input = void, Output = void>(
+  name: string,
+  {
+    order = DEFAULT_CONFIG_NAMESPACE_ORDER,
+    capabilities = [],
+    apply
+  }: ConfigNamespaceOptions<Output> = {}
+): ConfigNamespace<Input, Output> {
+  return { name, order, capabilities, apply };
+}
+
+/**
+ * The value a login sapling must resolve with.
+ */
+export interface LoginResult {
+  user: User;
+  /**
+   * Omitted when the keys are held by a `KeySession`, which signs for Canopy
+   * without handing it the private key.
+   */
+  keys?: KeyPair;
+}
+
+/**
+ * Logs the user in. Runs first, and stores the user and any keys it resolves
+ * with.
+ */
+export const LOGIN_NAMESPACE = defineConfigNamespace<void, LoginResult>(
+  'login',
+  {
+    order: 10,
+    capabilities: ['user:write', 'keys:write'],
+    apply: ({ user, keys }, canopy) => {
+      canopy.setUser(user);
+      if (keys) {
+        canopy.setKeys(keys);
+      }
+    }
+  }
+);
+
+/**
+ * Renders notifications. Runs after login.
+ */
+export const NOTIFICATIONS_NAMESPACE = defineConfigNamespace('notifications', {
+  order: 20
+});
+
+const BUILT_IN_NAMESPACES: {
+  [name in BuiltInConfigNamespace]: ConfigNamespace<unknown, unknown>;
+} = {
+  login: LOGIN_NAMESPACE,
+  notifications: NOTIFICATIONS_NAMESPACE
+};
+
+/**
+ * Returns the declaration of a namespace, looking up built-in namespaces that
+ * are referred to by name.
+ */
+export function resolveConfigNamespace(
+  configNamespace: ConfigNamespace<unknown, unknown> | BuiltInConfigNamespace
+): ConfigNamespace<unknown, unknown> {
+  return typeof configNamespace === 'string'
+    ? BUILT_IN_NAMESPACES[configNamespace]
+    : configNamespace;
+}
@@ -14,24 +14,808 @@
  * limitations under the License.
  */
 import sjcl from 'sjcl';
+import { ec as EC } from 'elliptic';
+import hash from 'hash.js';
+import { getPublicKey, KeyPair, sign } from './canopy';
+import { bytesToHex, hexToBytes, utf8Decode, utf8Encode } from './encoding';
+import { KeyDecryptionError, KeyFormatError } from './errors';
+
+const secp256k1 = new EC('secp256k1');
 
 /**
- * Encrypts a private key.
- * @param password - Encryption key.
- * @param privateKey - Unencrypted private key.
+ * Signs messages on behalf of a secp256k1 key, in the format expected by
+ * Splinter: the SHA-256 digest of the message is signed and the signature is
+ * returned as the 64-byte compact `r || s` encoding, in hex.
+ */
+export interface Signer {
+  /** Returns the compressed public key of the signer, in hex. */
+  getPublicKey(): string;
+  sign(message: Uint8Array): Promise<string>;
+  /** Checks that the signature was made by this signer over the message. */
+  verify(message: Uint8Array, signature: string): boolean;
+}
+
+/**
+ * Version of the key encryption format written by `encryptKey`.
+ */
+export const KEY_ENCRYPTION_VERSION = 2;
+
+/**
+ * PBKDF2 iteration count used by `encryptKey`, following the OWASP
+ * recommendation for PBKDF2-HMAC-SHA256.
+ */
+export const DEFAULT_PBKDF2_ITERATIONS = 600000;
+
+export interface KeyEncryptionOptions {
+  /** PBKDF2 iteration count, defaulting to `DEFAULT_PBKDF2_ITERATIONS`. */
+  iterations?: number;
+}
+
+/**
+ * Envelope written by `encryptKey`. The KDF and cipher parameters are stored
+ * alongside the ciphertext so that they can be strengthened in later versions
+ * without breaking existing keys.
+ */
+interface EncryptedKey {
+  version: number;
+  kdf: {
+    name: 'PBKDF2';
+    hash: 'SHA-256';
+    iterations: number;
+    salt: string;
+  };
+  cipher: {
+    name: 'AES-GCM';
+    iv: string;
+  };
+  ciphertext: string;
+}
+
+type ParsedKey =
+  | { legacy: false; envelope: EncryptedKey }
+  | { legacy: true; sjclData: string };
+
+function getSubtleCrypto(): SubtleCrypto {
+  if (typeof crypto === 'undefined' || !crypto.subtle) {
+    throw new Error('WebCrypto is not available in this environment');
+  }
+  return crypto.subtle;
+}
+
+function randomBytes(length: number): Uint8Array {
+  const bytes = new Uint8Array(length);
+  crypto.getRandomValues(bytes);
+  return bytes;
+}
+
+function toBase64(bytes: Uint8Array): string {
+  return sjcl.codec.base64.fromBits(sjcl.codec.hex.toBits(bytesToHex(bytes)));
+}
+
+function fromBase64(value: string): Uint8Array {
+  return hexToBytes(sjcl.codec.hex.fromBits(sjcl.codec.base64.toBits(value)));
+}
+
+function isEncryptedKeyEnvelope(value: unknown): value is EncryptedKey {
+  const envelope = value as EncryptedKey;
+  return (
+    typeof envelope.version === 'number' &&
+    !!envelope.kdf &&
+    envelope.kdf.name === 'PBKDF2' &&
+    envelope.kdf.hash === 'SHA-256' &&
+    typeof envelope.kdf.iterations === 'number' &&
+    envelope.kdf.iterations % 1 === 0 &&
+    envelope.kdf.iterations > 0 &&
+    typeof envelope.kdf.salt === 'string' &&
+    !!envelope.cipher &&
+    envelope.cipher.name === 'AES-GCM' &&
+    typeof envelope.cipher.iv === 'string' &&
+    typeof envelope.ciphertext === 'string'
+  );
+}
+
+/**
+ * Parses an encrypted key, recognising both the current envelope and the
+ * sjcl output written by earlier versions of SaplingJS, which was either the
+ * sjcl JSON itself or that JSON encoded again as a JSON string.
+ * @throws {KeyDecryptionError} if the key is in neither format
  */
-export function encryptKey(privateKey: string, password: string): string {
-  return JSON.stringify(sjcl.encrypt(password, privateKey));
+function parseEncryptedKey(encryptedPrivateKey: string): ParsedKey {
+  let parsed;
+  let sjclData = encryptedPrivateKey;
+  try {
+    parsed = JSON.parse(encryptedPrivateKey);
+    if (typeof parsed === 'string') {
+      sjclData = parsed;
+      parsed = JSON.parse(parsed);
+    }
+  } catch (err) {
+    throw new KeyDecryptionError('Unable to read the encrypted key');
+  }
+  if (parsed && typeof parsed.version === 'number') {
+    if (!isEncryptedKeyEnvelope(parsed)) {
+      throw new KeyDecryptionError('Malformed encrypted key envelope');
+    }
+    return { legacy: false, envelope: parsed };
+  }
+  if (parsed && typeof parsed.ct === 'string') {
+    return { legacy: true, sjclData };
+  }
+  throw new KeyDecryptionError('Unrecognized encrypted key format');
+}
+
+async function deriveKey(
+  password: string,
+  salt: Uint8Array,
+  iterations: number
+): Promise<CryptoKey> {
+  const subtle = getSubtleCrypto();
+  const baseKey = await subtle.importKey(
+    'raw',
+    utf8Encode(password),
+    'PBKDF2',
+    false,
+    ['deriveKey']
+  );
+  return subtle.deriveKey(
+    { name: 'PBKDF2', salt, iterations, hash: 'SHA-256' },
+    baseKey,
+    { name: 'AES-GCM', length: 256 },
+    false,
+    ['encrypt', 'decrypt']
+  );
 }
 
 /**
- * Decrypts a private key.
+ * Encrypts a private key with AES-GCM, using a key derived from the password
+ * with PBKDF2.
+ * @param privateKey - Unencrypted private key.
  * @param password - Encryption key.
+ * @param options - Key derivation options.
+ */
+export async function encryptKey(
+  privateKey: string,
+  password: string,
+  { iterations = DEFAULT_PBKDF2_ITERATIONS }: KeyEncryptionOptions = {}
+): Promise<string> {
+  const salt = randomBytes(16);
+  const iv = randomBytes(12);
+  const key = await deriveKey(password, salt, iterations);
+  const ciphertext = await getSubtleCrypto().encrypt(
+    { name: 'AES-GCM', iv },
+    key,
+    utf8Encode(privateKey)
+  );
+  const envelope: EncryptedKey = {
+    version: KEY_ENCRYPTION_VERSION,
+    kdf: { name: 'PBKDF2', hash: 'SHA-256', iterations, salt: toBase64(salt) },
+    cipher: { name: 'AES-GCM', iv: toBase64(iv) },
+    ciphertext: toBase64(new Uint8Array(ciphertext))
+  };
+  return JSON.stringify(envelope);
+}
+
+/**
+ * Decrypts a private key encrypted by `encryptKey`, including keys encrypted
+ * with sjcl by earlier versions of SaplingJS.
  * @param encryptedPrivateKey - Encrypted private key.
+ * @param password - Encryption key.
+ * @throws {KeyDecryptionError} if the password is wrong or the key corrupt
  */
-export function decryptKey(
+export async function decryptKey(
   encryptedPrivateKey: string,
   password: string
-): string {
-  return sjcl.decrypt(password, encryptedPrivateKey);
+): Promise<string> {
+  const parsed = parseEncryptedKey(encryptedPrivateKey);
+  if (parsed.legacy) {
+    try {
+      return sjcl.decrypt(password, parsed.sjclData);
+    } catch (err) {
+      throw new KeyDecryptionError('Unable to decrypt the key');
+    }
+  }
+
+  const { envelope } = parsed;
+  if (envelope.version !== KEY_ENCRYPTION_VERSION) {
+    throw new KeyDecryptionError(
+      `Unsupported key encryption version ${envelope.version}`
+    );
+  }
+  let salt: Uint8Array;
+  let iv: Uint8Array;
+  let ciphertext: Uint8Array;
+  try {
+    salt = fromBase64(envelope.kdf.salt);
+    iv = fromBase64(envelope.cipher.iv);
+    ciphertext = fromBase64(envelope.ciphertext);
+  } catch (err) {
+    throw new KeyDecryptionError('Malformed encrypted key envelope');
+  }
+  const key = await deriveKey(password, salt, envelope.kdf.iterations);
+  try {
+    const plaintext = await getSubtleCrypto().decrypt(
+      { name: 'AES-GCM', iv },
+      key,
+      ciphertext
+    );
+    return utf8Decode(new Uint8Array(plaintext));
+  } catch (err) {
+    throw new KeyDecryptionError('Unable to decrypt the key');
+  }
+}
+
+/**
+ * Checks whether an encrypted key should be decrypted and encrypted again,
+ * because it uses the legacy sjcl format or weaker parameters than the
+ * current defaults.
+ * @param encryptedPrivateKey - Encrypted private key.
+ * @param options - The key derivation options keys should meet.
+ * @throws {KeyDecryptionError} if the key is not an encrypted key
+ */
+export function needsRehash(
+  encryptedPrivateKey: string,
+  { iterations = DEFAULT_PBKDF2_ITERATIONS }: KeyEncryptionOptions = {}
+): boolean {
+  const parsed = parseEncryptedKey(encryptedPrivateKey);
+  if (parsed.legacy) {
+    return true;
+  }
+  const { envelope } = parsed;
+  return (
+    envelope.version < KEY_ENCRYPTION_VERSION ||
+    envelope.kdf.iterations < iterations
+  );
+}
+
+/**
+ * Returns the SHA-256 digest of the given bytes, in hex.
+ * @param data - Bytes to hash.
+ */
+export function sha256(data: Uint8Array): string {
+  return hash
+    .sha256()
+    .update(data)
+    .digest('hex');
+}
+
+/**
+ * Returns the SHA-512 digest of the given bytes, in hex.
+ * @param data - Bytes to hash.
+ */
+export function sha512(data: Uint8Array): string {
+  return hash
+    .sha512()
+    .update(data)
+    .digest('hex');
+}
+
+function digest(message: Uint8Array): number[] {
+  return hash
+    .sha256()
+    .update(message)
+    .digest();
+}
+
+function keyFromPrivate(privateKey: string): EC.KeyPair {
+  if (!/^[0-9a-fA-F]{64}$/.test(privateKey)) {
+    throw new Error('Private key must be 32 bytes, hex encoded');
+  }
+  const key = secp256k1.keyFromPrivate(privateKey, 'hex');
+  const scalar = key.getPrivate();
+  if (scalar.isZero() || scalar.cmp(secp256k1.n as typeof scalar) >= 0) {
+    throw new Error('Private key is out of range for secp256k1');
+  }
+  return key;
+}
+
+/**
+ * Generates a new secp256k1 key pair, in the hex format used by Splinter.
+ */
+export function generateKeyPair(): KeyPair {
+  const key = secp256k1.genKeyPair();
+  return {
+    publicKey: key.getPublic(true, 'hex'),
+    privateKey: key.getPrivate().toString('hex', 64)
+  };
+}
+
+/**
+ * Derives the compressed public key of a private key.
+ * @param privateKey - Hex encoded secp256k1 private key.
+ */
+export function publicKeyFromPrivate(privateKey: string): string {
+  return keyFromPrivate(privateKey).getPublic(true, 'hex');
+}
+
+/**
+ * Checks a compact hex signature over a message.
+ * @param message - The signed bytes.
+ * @param signature - Hex encoded `r || s` signature.
+ * @param publicKey - Hex encoded public key of the signer.
+ */
+export function verify(
+  message: Uint8Array,
+  signature: string,
+  publicKey: string
+): boolean {
+  if (!/^[0-9a-fA-F]{128}$/.test(signature)) {
+    return false;
+  }
+  try {
+    return secp256k1.verify(
+      digest(message),
+      { r: signature.slice(0, 64), s: signature.slice(64) },
+      secp256k1.keyFromPublic(publicKey, 'hex')
+    );
+  } catch (err) {
+    return false;
+  }
+}
+
+/**
+ * Creates a signer for a private key.
+ * @param privateKey - Hex encoded secp256k1 private key.
+ */
+export function createSigner(privateKey: string): Signer {
+  const key = keyFromPrivate(privateKey);
+  const publicKey = key.getPublic(true, 'hex');
+  return {
+    getPublicKey: (): string => publicKey,
+    sign: async (message: Uint8Array): Promise<string> => {
+      const signature = key.sign(digest(message), { canonical: true });
+      return signature.r.toString('hex', 64) + signature.s.toString('hex', 64);
+    },
+    verify: (message: Uint8Array, signature: string): boolean =>
+      verify(message, signature, publicKey)
+  };
+}
+
+/**
+ * Creates a signer for the keys set in Canopy. Messages are signed with the
+ * Canopy's `sign`, so the private key never leaves the host.
+ */
+export function canopySigner(): Signer {
+  return {
+    getPublicKey: (): string => getPublicKey(),
+    sign: (message: Uint8Array): Promise<string> => sign(message),
+    verify: (message: Uint8Array, signature: string): boolean =>
+      verify(message, signature, getPublicKey())
+  };
+}
+
+/**
+ * Options for exporting a private key. When a password is given, the exported
+ * key is encrypted with `encryptKey` and must be imported with the password.
+ */
+export interface KeyExportOptions extends KeyEncryptionOptions {
+  password?: string;
+}
+
+export interface PemExportOptions extends KeyExportOptions {
+  /**
+   * `pkcs8` writes a `PRIVATE KEY` block and `sec1` an `EC PRIVATE KEY`
+   * block, as written by `openssl ecparam -genkey`. Defaults to `pkcs8`.
+   */
+  pemFormat?: 'pkcs8' | 'sec1';
+}
+
+/**
+ * A secp256k1 key in the JSON Web Key format. The private key `d` is absent
+ * from public keys.
+ */
+export interface Secp256k1Jwk {
+  kty: 'EC';
+  crv: 'secp256k1';
+  x: string;
+  y: string;
+  d?: string;
+}
+
+function wrapExport(
+  text: string,
+  { password, iterations }: KeyExportOptions
+): Promise<string> {
+  return password === undefined
+    ? Promise.resolve(text)
+    : encryptKey(text, password, { iterations });
+}
+
+function importedKey(privateKey: string): EC.KeyPair {
+  try {
+    return keyFromPrivate(privateKey);
+  } catch (err) {
+    throw new KeyFormatError(err.message);
+  }
+}
+
+function keyPairOf(key: EC.KeyPair): KeyPair {
+  return {
+    publicKey: key.getPublic(true, 'hex'),
+    privateKey: key.getPrivate().toString('hex', 64)
+  };
+}
+
+/**
+ * Reads a private key file written by `splinter keygen`, which holds the key
+ * in hex on a single line.
+ * @param text - Contents of the `.priv` file.
+ * @throws {KeyFormatError} if the file does not hold a secp256k1 key
+ */
+export function importPrivateKeyHex(text: string): KeyPair {
+  return keyPairOf(importedKey(text.trim()));
+}
+
+/**
+ * Reads a public key file written by `splinter keygen`, returning the
+ * compressed key. Uncompressed keys are also accepted.
+ * @param text - Contents of the `.pub` file.
+ * @throws {KeyFormatError} if the file does not hold a secp256k1 point
+ */
+export function importPublicKeyHex(text: string): string {
+  const publicKey = text.trim();
+  if (!/^(0[23][0-9a-fA-F]{64}|04[0-9a-fA-F]{128})$/.test(publicKey)) {
+    throw new KeyFormatError(
+      'Public key must be a hex encoded secp256k1 point'
+    );
+  }
+  let key: EC.KeyPair;
+  try {
+    key = secp256k1.keyFromPublic(publicKey, 'hex');
+  } catch (err) {
+    throw new KeyFormatError('Public key is not on the secp256k1 curve');
+  }
+  if (!key.validate().result) {
+    throw new KeyFormatError('Public key is not on the secp256k1 curve');
+  }
+  return key.getPublic(true, 'hex');
+}
+
+/**
+ * Writes a private key in the format of the `.priv` files written by
+ * `splinter keygen`.
+ * @param privateKey - Hex encoded secp256k1 private key.
+ * @param options - Encrypts the file when a password is given.
+ */
+export function exportPrivateKeyHex(
+  privateKey: string,
+  options: KeyExportOptions = {}
+): Promise<string> {
+  return wrapExport(
+    `${keyPairOf(importedKey(privateKey)).privateKey}\n`,
+    options
+  );
+}
+
+/**
+ * Writes a public key in the format of the `.pub` files written by
+ * `splinter keygen`.
+ * @param publicKey - Hex encoded secp256k1 public key.
+ */
+export function exportPublicKeyHex(publicKey: string): string {
+  return `${importPublicKeyHex(publicKey)}\n`;
+}
+
+const DER_INTEGER = 0x02;
+const DER_BIT_STRING = 0x03;
+const DER_OCTET_STRING = 0x04;
+const DER_OID = 0x06;
+const DER_SEQUENCE = 0x30;
+const DER_EXPLICIT_0 = 0xa0;
+const DER_EXPLICIT_1 = 0xa1;
+
+// 1.2.840.10045.2.1
+const EC_PUBLIC_KEY_OID = [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+// 1.3.132.0.10
+const SECP256K1_OID = [0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+interface DerNode {
+  tag: number;
+  content: Uint8Array;
+}
+
+function derEncode(tag: number, content: number[]): number[] {
+  const { length } = content;
+  if (length < 0x80) {
+    return [tag, length, ...content];
+  }
+  if (length < 0x100) {
+    return [tag, 0x81, length, ...content];
+  }
+  return [tag, 0x82, Math.floor(length / 0x100), length % 0x100, ...content];
+}
+
+/**
+ * Splits DER encoded bytes into the nodes they contain, without descending
+ * into them.
+ */
+function derDecode(bytes: Uint8Array): DerNode[] {
+  const nodes: DerNode[] = [];
+  let offset = 0;
+  while (offset < bytes.length) {
+    if (offset + 2 > bytes.length) {
+      throw new KeyFormatError('Malformed DER encoding');
+    }
+    const tag = bytes[offset];
+    let length = bytes[offset + 1];
+    offset += 2;
+    if (length >= 0x80) {
+      const lengthBytes = length - 0x80;
+      if (lengthBytes > 2 || offset + lengthBytes > bytes.length) {
+        throw new KeyFormatError('Malformed DER encoding');
+      }
+      length = 0;
+      for (let i = 0; i < lengthBytes; i += 1) {
+        length = length * 0x100 + bytes[offset + i];
+      }
+      offset += lengthBytes;
+    }
+    if (offset + length > bytes.length) {
+      throw new KeyFormatError('Malformed DER encoding');
+    }
+    nodes.push({ tag, content: bytes.subarray(offset, offset + length) });
+    offset += length;
+  }
+  return nodes;
+}
+
+function isDerNode(
+  node: DerNode | undefined,
+  tag: number,
+  content?: number[]
+): node is DerNode {
+  return (
+    node !== undefined &&
+    node.tag === tag &&
+    (content === undefined || bytesToHex(node.content) === bytesToHex(content))
+  );
+}
+
+function derSequence(bytes: Uint8Array): DerNode[] {
+  const nodes = derDecode(bytes);
+  if (nodes.length !== 1 || !isDerNode(nodes[0], DER_SEQUENCE)) {
+    throw new KeyFormatError('Malformed DER encoding');
+  }
+  return derDecode(nodes[0].content);
+}
+
+function checkCurve(curve: DerNode | undefined): void {
+  if (!isDerNode(curve, DER_OID, SECP256K1_OID)) {
+    throw new KeyFormatError('Only keys on the secp256k1 curve are supported');
+  }
+}
+
+function uncompressedPublicKey(key: EC.KeyPair): number[] {
+  return Array.from(hexToBytes(key.getPublic(false, 'hex')));
+}
+
+// Encodes a SEC1 ECPrivateKey, with the public key and, optionally, the curve.
+function encodeEcPrivateKey(key: EC.KeyPair, withCurve: boolean): number[] {
+  const curve = withCurve
+    ? derEncode(DER_EXPLICIT_0, derEncode(DER_OID, SECP256K1_OID))
+    : [];
+  return derEncode(DER_SEQUENCE, [
+    ...derEncode(DER_INTEGER, [1]),
+    ...derEncode(DER_OCTET_STRING, key.getPrivate().toArray('be', 32)),
+    ...curve,
+    ...derEncode(
+      DER_EXPLICIT_1,
+      derEncode(DER_BIT_STRING, [0, ...uncompressedPublicKey(key)])
+    )
+  ]);
+}
+
+function decodeEcPrivateKey(bytes: Uint8Array): KeyPair {
+  const [version, privateKey, ...fields] = derSequence(bytes);
+  if (
+    !isDerNode(version, DER_INTEGER, [1]) ||
+    !isDerNode(privateKey, DER_OCTET_STRING)
+  ) {
+    throw new KeyFormatError('Malformed EC private key');
+  }
+  const keys = keyPairOf(
+    importedKey(bytesToHex(privateKey.content).padStart(64, '0'))
+  );
+  fields.forEach(field => {
+    if (field.tag === DER_EXPLICIT_0) {
+      checkCurve(derDecode(field.content)[0]);
+    } else if (field.tag === DER_EXPLICIT_1) {
+      const publicKey = derDecode(field.content)[0];
+      if (
+        !isDerNode(publicKey, DER_BIT_STRING) ||
+        importPublicKeyHex(bytesToHex(publicKey.content.subarray(1))) !==
+          keys.publicKey
+      ) {
+        throw new KeyFormatError(
+          'The public key does not match the private key'
+        );
+      }
+    }
+  });
+  return keys;
+}
+
+function encodePkcs8(key: EC.KeyPair): number[] {
+  return derEncode(DER_SEQUENCE, [
+    ...derEncode(DER_INTEGER, [0]),
+    ...derEncode(DER_SEQUENCE, [
+      ...derEncode(DER_OID, EC_PUBLIC_KEY_OID),
+      ...derEncode(DER_OID, SECP256K1_OID)
+    ]),
+    ...derEncode(DER_OCTET_STRING, encodeEcPrivateKey(key, false))
+  ]);
+}
+
+function decodePkcs8(bytes: Uint8Array): KeyPair {
+  const [version, algorithm, privateKey] = derSequence(bytes);
+  if (
+    !isDerNode(version, DER_INTEGER, [0]) ||
+    !isDerNode(algorithm, DER_SEQUENCE) ||
+    !isDerNode(privateKey, DER_OCTET_STRING)
+  ) {
+    throw new KeyFormatError('Malformed PKCS#8 private key');
+  }
+  const [keyType, curve] = derDecode(algorithm.content);
+  if (!isDerNode(keyType, DER_OID, EC_PUBLIC_KEY_OID)) {
+    throw new KeyFormatError('Only EC private keys are supported');
+  }
+  checkCurve(curve);
+  return decodeEcPrivateKey(privateKey.content);
+}
+
+const PEM_PATTERN = /-----BEGIN ((?:EC |ENCRYPTED )?PRIVATE KEY)-----([\s\S]*?)-----END \1-----/;
+
+/**
+ * Reads a PEM private key, in either the PKCS#8 `PRIVATE KEY` or the SEC1
+ * `EC PRIVATE KEY` format. Other blocks in the text, such as the
+ * `EC PARAMETERS` written by OpenSSL, are ignored.
+ * @param text - Contents of the PEM file.
+ * @throws {KeyFormatError} if the file does not hold a secp256k1 private key
+ */
+export function importPrivateKeyPem(text: string): KeyPair {
+  const match = PEM_PATTERN.exec(text);
+  if (!match) {
+    throw new KeyFormatError('No PEM private key found');
+  }
+  if (match[1] === 'ENCRYPTED PRIVATE KEY') {
+    throw new KeyFormatError('PEM keys encrypted by OpenSSL are not supported');
+  }
+  let der: Uint8Array;
+  try {
+    der = fromBase64(match[2].replace(/\s/g, ''));
+  } catch (err) {
+    throw new KeyFormatError('PEM key is not valid base64');
+  }
+  return match[1] === 'EC PRIVATE KEY'
+    ? decodeEcPrivateKey(der)
+    : decodePkcs8(der);
+}
+
+function pemEncode(label: string, der: number[]): string {
+  const lines = toBase64(new Uint8Array(der)).match(/.{1,64}/g) || [];
+  return `-----BEGIN ${label}-----\n${lines.join('\n')}\n-----END ${label}-----\n`;
+}
+
+/**
+ * Writes a private key as PEM, readable by OpenSSL.
+ * @param privateKey - Hex encoded secp256k1 private key.
+ * @param options - The PEM format, and a password to encrypt the file with.
+ */
+export function exportPrivateKeyPem(
+  privateKey: string,
+  { pemFormat = 'pkcs8', ...options }: PemExportOptions = {}
+): Promise<string> {
+  const key = importedKey(privateKey);
+  const pem =
+    pemFormat === 'sec1'
+      ? pemEncode('EC PRIVATE KEY', encodeEcPrivateKey(key, true))
+      : pemEncode('PRIVATE KEY', encodePkcs8(key));
+  return wrapExport(pem, options);
+}
+
+function toBase64Url(bytes: number[]): string {
+  return toBase64(new Uint8Array(bytes))
+    .replace(/\+/g, '-')
+    .replace(/\//g, '_')
+    .replace(/=+$/, '');
+}
+
+function fromBase64Url(value: unknown, member: string): string {
+  if (typeof value !== 'string' || !/^[A-Za-z0-9_-]+$/.test(value)) {
+    throw new KeyFormatError(`JWK member '${member}' must be base64url`);
+  }
+  return bytesToHex(fromBase64(value.replace(/-/g, '+').replace(/_/g, '/')));
+}
+
+function jwkOf(key: EC.KeyPair): Secp256k1Jwk {
+  const point = key.getPublic();
+  return {
+    kty: 'EC',
+    crv: 'secp256k1',
+    x: toBase64Url(point.getX().toArray('be', 32)),
+    y: toBase64Url(point.getY().toArray('be', 32)),
+    d: toBase64Url(key.getPrivate().toArray('be', 32))
+  };
+}
+
+/**
+ * Reads a secp256k1 private key in the JSON Web Key format. When the key also
+ * has its public coordinates, they must match the private key.
+ * @param jwk - The JWK, or its JSON.
+ * @throws {KeyFormatError} if the JWK is not a secp256k1 private key
+ */
+export function importPrivateKeyJwk(jwk: string | Secp256k1Jwk): KeyPair {
+  let parsed: Partial<Secp256k1Jwk> | null;
+  try {
+    parsed = typeof jwk === 'string' ? JSON.parse(jwk) : jwk;
+  } catch (err) {
+    throw new KeyFormatError('JWK is not valid JSON');
+  }
+  if (!parsed || parsed.kty !== 'EC' || parsed.crv !== 'secp256k1') {
+    throw new KeyFormatError('JWK must be an EC key on the secp256k1 curve');
+  }
+  if (parsed.d === undefined) {
+    throw new KeyFormatError('JWK does not contain a private key');
+  }
+  const key = importedKey(fromBase64Url(parsed.d, 'd'));
+  if (parsed.x !== undefined || parsed.y !== undefined) {
+    const expected = jwkOf(key);
+    if (
+      fromBase64Url(parsed.x, 'x') !== fromBase64Url(expected.x, 'x') ||
+      fromBase64Url(parsed.y, 'y') !== fromBase64Url(expected.y, 'y')
+    ) {
+      throw new KeyFormatError('The public key does not match the private key');
+    }
+  }
+  return keyPairOf(key);
+}
+
+/**
+ * Writes a private key as a JSON Web Key, with its public coordinates.
+ * @param privateKey - Hex encoded secp256k1 private key.
+ * @param options - Encrypts the JWK when a password is given.
+ */
+export function exportPrivateKeyJwk(
+  privateKey: string,
+  options: KeyExportOptions = {}
+): Promise<string> {
+  return wrapExport(JSON.stringify(jwkOf(importedKey(privateKey))), options);
+}
+
+function isEncryptedKey(text: string): boolean {
+  try {
+    parseEncryptedKey(text);
+    return true;
+  } catch (err) {
+    return false;
+  }
+}
+
+/**
+ * Reads a private key in any of the formats written by the export functions,
+ * detecting the format from the text. Keys exported with a password are
+ * decrypted first.
+ * @param text - Contents of the key file.
+ * @param password - The password the key was exported with, if any.
+ * @throws {KeyDecryptionError} if the key is encrypted and the password is
+ * missing or wrong
+ * @throws {KeyFormatError} if the key cannot be read
+ */
+export async function importPrivateKey(
+  text: string,
+  password?: string
+): Promise<KeyPair> {
+  let plaintext = text.trim();
+  if (isEncryptedKey(plaintext)) {
+    if (password === undefined) {
+      throw new KeyDecryptionError('A password is required for this key');
+    }
+    plaintext = (await decryptKey(plaintext, password)).trim();
+  }
+  if (plaintext.indexOf('-----BEGIN') !== -1) {
+    return importPrivateKeyPem(plaintext);
+  }
+  if (plaintext.charAt(0) === '{') {
+    return importPrivateKeyJwk(plaintext);
+  }
+  return importPrivateKeyHex(plaintext);
 }
@@ -0,0 +1,113 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { parseSharedConfig, startDevHost, storageStore } from './devHost';
+import { getSharedConfig, getUser, setUser } from './canopy';
+import { PermissionDeniedError } from './errors';
+import { Transport } from './transport';
+
+function configTransport(body: string): Transport {
+  return async () => ({ status: 200, headers: {}, body });
+}
+
+afterEach(() => {
+  window.sessionStorage.clear();
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  delete (window as any).$CANOPY;
+});
+
+describe('parseSharedConfig(text)', () => {
+  it('should parse YAML and JSON config files', () => {
+    const expected = { canopyConfig: { splinterURL: 'http://localhost:8085' } };
+    expect(
+      parseSharedConfig('canopyConfig:\n  splinterURL: http://localhost:8085\n')
+    ).toEqual(expected);
+    expect(parseSharedConfig(JSON.stringify(expected))).toEqual(expected);
+  });
+
+  it('should reject a config without a Splinter URL', () => {
+    expect(() => parseSharedConfig('canopyConfig: {}')).toThrow(
+      'canopyConfig.splinterURL'
+    );
+  });
+});
+
+describe('startDevHost(options)', () => {
+  const transport = configTransport(
+    'canopyConfig:\n  splinterURL: http://localhost:8085\n'
+  );
+
+  it('should run the config saplings in order, then mount the app', async () => {
+    const calls: string[] = [];
+    const domNode = document.createElement('div');
+    await startDevHost({
+      transport,
+      domNode,
+      manifests: [
+        {
+          name: 'sapling',
+          configNamespaces: ['login'],
+          capabilities: ['config:read', 'user:write', 'keys:write']
+        }
+      ],
+      loadSaplings: () => {
+        // eslint-disable-next-line @typescript-eslint/no-explicit-any
+        const canopy = (window as any).$CANOPY;
+        canopy.registerApp(
+          (node: Node) => calls.push(`app:${node === domNode}`),
+          { name: 'sapling', capabilities: ['config:read'] }
+        );
+        canopy.registerConfigSapling('notifications', () =>
+          calls.push('notifications')
+        );
+        canopy.registerConfigSapling('login', () => calls.push('login'));
+      }
+    });
+    expect(getSharedConfig().canopyConfig.splinterURL).toEqual(
+      'http://localhost:8085'
+    );
+    expect(calls).toEqual(['login', 'notifications', 'app:true']);
+  });
+
+  it('should not grant capabilities missing from the manifests', async () => {
+    await expect(
+      startDevHost({
+        transport,
+        loadSaplings: () => {
+          // eslint-disable-next-line @typescript-eslint/no-explicit-any
+          const canopy = (window as any).$CANOPY;
+          canopy.registerApp(jest.fn(), {
+            name: 'sapling',
+            capabilities: ['keys:read']
+          });
+        }
+      })
+    ).rejects.toBeInstanceOf(PermissionDeniedError);
+  });
+
+  it('should keep the user in session storage across reloads', async () => {
+    await startDevHost({ transport });
+    setUser({ userId: 'alice' });
+    await startDevHost({ transport });
+    expect(getUser()).toEqual({ userId: 'alice' });
+  });
+});
+
+describe('storageStore(storage)', () => {
+  it('should ignore unreadable stored state', () => {
+    window.sessionStorage.setItem('saplingjs.devHost', 'not json');
+    expect(storageStore(window.sessionStorage).load()).toEqual({});
+  });
+});
@@ -0,0 +1,154 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import yaml from 'js-yaml';
+
+import { SharedConfig } from './canopy';
+import {
+  ApprovedManifest,
+  CanopyHost,
+  CanopyHostState,
+  CanopyHostStore,
+  createCanopyHost
+} from './host';
+import { http } from './http';
+import { browserHistory } from './routing';
+import { Transport } from './transport';
+
+export const DEFAULT_STORAGE_KEY = 'saplingjs.devHost';
+
+export interface DevHostOptions {
+  /** URL of the YAML or JSON file holding the shared config. */
+  configURL?: string;
+  /** DOM node the registered app is mounted into. */
+  domNode?: Node;
+  /** Storage the user and keys are kept in. Defaults to session storage. */
+  storage?: Storage;
+  /** Key the user and keys are stored under. */
+  storageKey?: string;
+  /** Transport used to fetch the config file. */
+  transport?: Transport;
+  /**
+   * Loads the sapling scripts once `window.$CANOPY` is installed, such as
+   * `() => import('./sapling')`.
+   */
+  loadSaplings?: () => unknown;
+  /** Inputs of the config saplings, keyed by namespace name. */
+  configInputs?: { [namespace: string]: unknown };
+  /**
+   * Manifests of the saplings, granting the capabilities they are registered
+   * with, such as those returned by `loadManifest`.
+   */
+  manifests?: ApprovedManifest[];
+}
+
+/**
+ * Parses the shared config from the contents of a YAML or JSON file, which
+ * must contain at least `canopyConfig.splinterURL`.
+ */
+export function parseSharedConfig(text: string): SharedConfig {
+  const config = yaml.safeLoad(text) as SharedConfig | undefined;
+  if (
+    !config ||
+    !config.canopyConfig ||
+    typeof config.canopyConfig.splinterURL !== 'string'
+  ) {
+    throw new Error('Shared config must define canopyConfig.splinterURL');
+  }
+  return config;
+}
+
+/**
+ * Stores the user and keys as JSON in the given storage.
+ */
+export function storageStore(
+  storage: Storage,
+  storageKey = DEFAULT_STORAGE_KEY
+): CanopyHostStore {
+  return {
+    load: () => {
+      const stored = storage.getItem(storageKey);
+      if (!stored) {
+        return {};
+      }
+      try {
+        return JSON.parse(stored) as CanopyHostState;
+      } catch (err) {
+        return {};
+      }
+    },
+    save: state => {
+      storage.setItem(storageKey, JSON.stringify(state));
+    }
+  };
+}
+
+/**
+ * Runs a sapling outside of a full Canopy app, against a local Splinter node.
+ *
+ * Fetches the shared config, installs a working `window.$CANOPY` that keeps
+ * the user and keys in session storage, and loads the saplings. The config
+ * saplings are then run in the order of their namespaces, starting with
+ * 'login' and 'notifications', and the app whose route prefix matches the
+ * current URL, or else the last registered app, is mounted into `domNode`.
+ * The app is suspended while the page is hidden.
+ *
+ * @example
+ * startDevHost({
+ *   configURL: '/canopy-config.yaml',
+ *   domNode: document.getElementById('root'),
+ *   loadSaplings: () => import('./index')
+ * });
+ */
+export async function startDevHost({
+  configURL = '/canopy-config.yaml',
+  domNode = document.body,
+  storage = window.sessionStorage,
+  storageKey,
+  transport,
+  loadSaplings,
+  configInputs,
+  manifests
+}: DevHostOptions = {}): Promise<CanopyHost> {
+  const response = await http('GET', configURL, null, { transport });
+  const sharedConfig = parseSharedConfig(response.body);
+
+  const host = createCanopyHost({
+    sharedConfig,
+    store: storageStore(storage, storageKey),
+    history: browserHistory(),
+    manifests
+  });
+  // In order to prevent the need to overwrite the window interface,
+  // a intentional `any` is cast here.
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  (window as any).$CANOPY = host.windowCanopy;
+
+  if (loadSaplings) {
+    await loadSaplings();
+  }
+
+  await host.runConfigSaplings(configInputs);
+
+  if (host.apps.length > 0) {
+    host.mountApp(domNode);
+  }
+  document.addEventListener('visibilitychange', () =>
+    document.visibilityState === 'hidden'
+      ? host.suspendApp()
+      : host.resumeApp()
+  );
+  return host;
+}
@@ -0,0 +1,106 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/**
+ * Encodes a string as UTF-8 bytes.
+ */
+export function utf8Encode(value: string): Uint8Array {
+  const bytes: number[] = [];
+  for (let i = 0; i < value.length; i += 1) {
+    let code = value.charCodeAt(i);
+    // Combine surrogate pairs into a single code point.
+    if (code >= 0xd800 && code < 0xdc00 && i + 1 < value.length) {
+      const next = value.charCodeAt(i + 1);
+      if (next >= 0xdc00 && next < 0xe000) {
+        code = 0x10000 + (code - 0xd800) * 0x400 + (next - 0xdc00);
+        i += 1;
+      }
+    }
+    if (code < 0x80) {
+      bytes.push(code);
+    } else if (code < 0x800) {
+      bytes.push(0xc0 + Math.floor(code / 0x40), 0x80 + (code % 0x40));
+    } else if (code < 0x10000) {
+      bytes.push(
+        0xe0 + Math.floor(code / 0x1000),
+        0x80 + (Math.floor(code / 0x40) % 0x40),
+        0x80 + (code % 0x40)
+      );
+    } else {
+      bytes.push(
+        0xf0 + Math.floor(code / 0x40000),
+        0x80 + (Math.floor(code / 0x1000) % 0x40),
+        0x80 + (Math.floor(code / 0x40) % 0x40),
+        0x80 + (code % 0x40)
+      );
+    }
+  }
+  return new Uint8Array(bytes);
+}
+
+/**
+ * Decodes UTF-8 bytes into a string.
+ */
+export function utf8Decode(bytes: Uint8Array): string {
+  let result = '';
+  let i = 0;
+  while (i < bytes.length) {
+    const lead = bytes[i];
+    let extra = 0;
+    let code = lead;
+    if (lead >= 0xf0) {
+      extra = 3;
+      code = lead - 0xf0;
+    } else if (lead >= 0xe0) {
+      extra = 2;
+      code = lead - 0xe0;
+    } else if (lead >= 0xc0) {
+      extra = 1;
+      code = lead - 0xc0;
+    }
+    for (let j = 1; j <= extra; j += 1) {
+      code = code * 0x40 + (bytes[i + j] - 0x80);
+    }
+    result += String.fromCodePoint(code);
+    i += extra + 1;
+  }
+  return result;
+}
+
+/**
+ * Encodes bytes as a lower-case hex string.
+ */
+export function bytesToHex(bytes: Uint8Array | number[]): string {
+  let hex = '';
+  for (let i = 0; i < bytes.length; i += 1) {
+    hex += `0${bytes[i].toString(16)}`.slice(-2);
+  }
+  return hex;
+}
+
+/**
+ * Decodes a hex string into bytes.
+ */
+export function hexToBytes(hex: string): Uint8Array {
+  if (hex.length % 2 !== 0 || !/^[0-9a-fA-F]*$/.test(hex)) {
+    throw new Error('Invalid hex string');
+  }
+  const bytes = new Uint8Array(hex.length / 2);
+  for (let i = 0; i < bytes.length; i += 1) {
+    bytes[i] = parseInt(hex.slice(i * 2, i * 2 + 2), 16);
+  }
+  return bytes;
+}
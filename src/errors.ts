@@ -0,0 +1,302 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+/* eslint-disable max-classes-per-file */
+
+export interface HttpErrorDetails {
+  method: string;
+  url: string;
+  status: number;
+  body: string;
+  headers?: { [name: string]: string };
+}
+
+function parseBody(body: string): unknown {
+  try {
+    return JSON.parse(body);
+  } catch (err) {
+    return undefined;
+  }
+}
+
+/**
+ * Base class for every error raised while talking to a Splinter node.
+ */
+export class SplinterHttpError extends Error {
+  /** HTTP status code of the response, or 0 if no response was received. */
+  status: number;
+
+  url: string;
+
+  method: string;
+
+  /** The raw response body. */
+  body: string;
+
+  /** The response body parsed as JSON, if it was valid JSON. */
+  data: unknown;
+
+  /** Response headers, keyed by lower-case header name. */
+  headers: { [name: string]: string };
+
+  constructor(
+    message: string,
+    { method, url, status, body, headers = {} }: HttpErrorDetails
+  ) {
+    super(message);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'SplinterHttpError';
+    this.method = method;
+    this.url = url;
+    this.status = status;
+    this.body = body;
+    this.data = parseBody(body);
+    this.headers = headers;
+  }
+}
+
+/**
+ * Raised when a Splinter node responds with a 4xx status.
+ */
+export class SplinterClientError extends SplinterHttpError {
+  constructor(message: string, details: HttpErrorDetails) {
+    super(message, details);
+    this.name = 'SplinterClientError';
+  }
+}
+
+/**
+ * Raised when an authenticated request is rejected with a 401 status, meaning
+ * the user's token is no longer accepted and they must log in again.
+ */
+export class SplinterSessionExpiredError extends SplinterClientError {
+  constructor(message: string, details: HttpErrorDetails) {
+    super(message, details);
+    this.name = 'SplinterSessionExpiredError';
+  }
+}
+
+/**
+ * Raised when a Splinter node responds with a 5xx status.
+ */
+export class SplinterServerError extends SplinterHttpError {
+  constructor(message: string, details: HttpErrorDetails) {
+    super(message, details);
+    this.name = 'SplinterServerError';
+  }
+}
+
+/**
+ * Raised when a request could not reach the Splinter node at all.
+ */
+export class SplinterNetworkError extends SplinterHttpError {
+  constructor(message: string, details: HttpErrorDetails) {
+    super(message, details);
+    this.name = 'SplinterNetworkError';
+  }
+}
+
+/**
+ * Raised when no response was received before the request timed out.
+ */
+export class SplinterTimeoutError extends SplinterNetworkError {
+  constructor(message: string, details: HttpErrorDetails) {
+    super(message, details);
+    this.name = 'SplinterTimeoutError';
+  }
+}
+
+/**
+ * Raised when a request is cancelled through its `AbortSignal`.
+ */
+export class SplinterAbortError extends SplinterHttpError {
+  constructor(message: string, details: HttpErrorDetails) {
+    super(message, details);
+    this.name = 'SplinterAbortError';
+  }
+}
+
+/**
+ * Raised when a successful response does not contain the expected JSON.
+ */
+export class SplinterParseError extends SplinterHttpError {
+  constructor(message: string, details: HttpErrorDetails) {
+    super(message, details);
+    this.name = 'SplinterParseError';
+  }
+}
+
+/**
+ * Builds the error matching an unsuccessful response. Splinter reports errors
+ * as `{ "message": "..." }`, which is used as the error message when present.
+ */
+export function httpErrorFromResponse(
+  details: HttpErrorDetails,
+  authenticated = false
+): SplinterHttpError {
+  const data = parseBody(details.body) as { message?: unknown } | undefined;
+  const splinterMessage =
+    data && typeof data.message === 'string' ? data.message : null;
+
+  if (details.status === 401 && authenticated) {
+    return new SplinterSessionExpiredError(
+      splinterMessage || 'Your session has expired. Please log in again.',
+      details
+    );
+  }
+  if (details.status >= 400 && details.status < 500) {
+    return new SplinterClientError(
+      splinterMessage ||
+        'Failed to send request. Contact the administrator for help.',
+      details
+    );
+  }
+  if (details.status >= 500) {
+    return new SplinterServerError(
+      splinterMessage ||
+        'The server has encountered an error. Please contact the administrator.',
+      details
+    );
+  }
+  return new SplinterHttpError(
+    splinterMessage || `Unexpected response status ${details.status}`,
+    details
+  );
+}
+
+/**
+ * Raised when a circuit proposal fails validation before it is submitted.
+ */
+export class CircuitProposalError extends Error {
+  /** Every problem found with the proposal. */
+  errors: string[];
+
+  constructor(errors: string[]) {
+    super(`Invalid circuit proposal: ${errors.join('; ')}`);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'CircuitProposalError';
+    this.errors = errors;
+  }
+}
+
+/**
+ * Raised when an encrypted key cannot be decrypted, either because the
+ * password is wrong or because the key is corrupt.
+ */
+export class KeyDecryptionError extends Error {
+  constructor(message: string) {
+    super(message);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'KeyDecryptionError';
+  }
+}
+
+/**
+ * Raised when a Canopy function is called outside of a Canopy, where
+ * `window.$CANOPY` is not in scope.
+ */
+export class CanopyUnavailableError extends Error {
+  constructor(message: string) {
+    super(message);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'CanopyUnavailableError';
+  }
+}
+
+/**
+ * Raised when a request sent to another sapling gets no reply, either because
+ * no mounted sapling replies to the topic or because the reply timed out.
+ */
+export class SaplingMessageError extends Error {
+  /** The topic the request was sent to. */
+  topic: string;
+
+  constructor(message: string, topic: string) {
+    super(message);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'SaplingMessageError';
+    this.topic = topic;
+  }
+}
+
+/**
+ * Raised when a sapling manifest is malformed, or requires a newer Canopy API
+ * than the host provides.
+ */
+export class ManifestError extends Error {
+  /** Every problem found with the manifest. */
+  errors: string[];
+
+  constructor(errors: string[]) {
+    super(`Invalid sapling manifest: ${errors.join('; ')}`);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'ManifestError';
+    this.errors = errors;
+  }
+}
+
+/**
+ * Raised when a sapling calls a Canopy function it was not granted the
+ * capability for.
+ */
+export class PermissionDeniedError extends Error {
+  /** The capability the sapling lacks. */
+  capability: string;
+
+  saplingName: string;
+
+  constructor(saplingName: string, capability: string) {
+    super(`Sapling '${saplingName}' lacks the '${capability}' capability`);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'PermissionDeniedError';
+    this.saplingName = saplingName;
+    this.capability = capability;
+  }
+}
+
+/**
+ * Raised when the keys of a key session are used while it is locked.
+ */
+export class KeysLockedError extends Error {
+  constructor(message: string) {
+    super(message);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'KeysLockedError';
+  }
+}
+
+/**
+ * Raised when a keystore operation refers to a missing key or would store a
+ * key twice.
+ */
+export class KeystoreError extends Error {
+  constructor(message: string) {
+    super(message);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'KeystoreError';
+  }
+}
+
+/**
+ * Raised when an imported key is malformed, is not a secp256k1 key, or its
+ * public key does not match its private key.
+ */
+export class KeyFormatError extends Error {
+  constructor(message: string) {
+    super(message);
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'KeyFormatError';
+  }
+}
@@ -0,0 +1,63 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/**
+ * Stops a listener from being called. Calling it more than once is harmless.
+ */
+export interface Unsubscribe {
+  (): void;
+}
+
+export interface Listener<T> {
+  (value: T): void;
+}
+
+/**
+ * Calls a listener, rethrowing anything it throws asynchronously so that one
+ * failing listener does not prevent the others from being called, while its
+ * error is still reported.
+ */
+export function callListener<T>(listener: Listener<T>, value: T): void {
+  try {
+    listener(value);
+  } catch (err) {
+    setTimeout(() => {
+      throw err;
+    });
+  }
+}
+
+/**
+ * Calls every subscribed listener with each emitted value, in the order the
+ * listeners subscribed.
+ */
+export class Emitter<T> {
+  private listeners: Listener<T>[] = [];
+
+  subscribe(listener: Listener<T>): Unsubscribe {
+    this.listeners.push(listener);
+    return () => {
+      this.listeners = this.listeners.filter(other => other !== listener);
+    };
+  }
+
+  /**
+   * Calls the listeners subscribed at the time of the call.
+   */
+  emit(value: T): void {
+    this.listeners.slice().forEach(listener => callListener(listener, value));
+  }
+}
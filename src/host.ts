@@ -0,0 +1,494 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  AppBootstrap,
+  BuiltInConfigNamespace,
+  Canopy,
+  Capability,
+  ConfigNamespace,
+  ConfigSaplingBootstrap,
+  SaplingLifecycle,
+  KeyPair,
+  KeySigner,
+  SharedConfig,
+  User
+} from './canopy';
+import { resolveConfigNamespace } from './configNamespaces';
+import { createSigner } from './crypto';
+import { Emitter, Unsubscribe } from './events';
+import { MessageBus } from './messaging';
+import { NotificationCenter } from './notifications';
+import { KeysLockedError, PermissionDeniedError } from './errors';
+import { SaplingManifest } from './manifest';
+import {
+  guardCanopy,
+  guardCanopyForCaller,
+  hasCapability
+} from './permissions';
+import {
+  createRouter,
+  matchesRoutePrefix,
+  memoryHistory,
+  normalizeRoutePrefix,
+  RouteHistory
+} from './routing';
+
+function toLifecycle(result: ReturnType<AppBootstrap>): SaplingLifecycle {
+  if (typeof result === 'function') {
+    return { unmount: result };
+  }
+  return result || {};
+}
+
+/**
+ * An app registered with `registerApp`.
+ */
+export interface RegisteredApp {
+  name: string;
+  routePrefix: string;
+  capabilities: ReadonlyArray<Capability>;
+  bootstrapFunction: AppBootstrap;
+}
+
+/**
+ * A sapling registered with `registerConfigSapling`.
+ */
+export interface RegisteredConfigSapling {
+  configNamespace: ConfigNamespace<unknown, unknown>;
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  bootstrapFunction: ConfigSaplingBootstrap<any, unknown>;
+  /**
+   * False for saplings registered by the name of a built-in namespace, which
+   * have no contract and whose output is ignored.
+   */
+  typed: boolean;
+}
+
+/**
+ * The value a config sapling resolved with.
+ */
+export interface ConfigSaplingResult {
+  configNamespace: ConfigNamespace<unknown, unknown>;
+  output: unknown;
+}
+
+/**
+ * The user and keys held by a host.
+ */
+export interface CanopyHostState {
+  user?: User;
+  keys?: KeyPair;
+}
+
+/**
+ * Persists the user and keys of a host, such as in session storage.
+ */
+export interface CanopyHostStore {
+  load(): CanopyHostState;
+  save(state: CanopyHostState): void;
+}
+
+/**
+ * The parts of a sapling's manifest a host grants capabilities by.
+ */
+export type ApprovedManifest = Pick<
+  SaplingManifest,
+  'name' | 'capabilities' | 'configNamespaces'
+>;
+
+export interface CanopyHostOptions {
+  sharedConfig: SharedConfig;
+  user?: User;
+  keys?: KeyPair;
+  store?: CanopyHostStore;
+  /** Defaults to an in-memory history starting at `/`. */
+  history?: RouteHistory;
+  /**
+   * Manifests of the saplings the host has approved. An app can only be
+   * registered with capabilities listed by the manifest of the same name, and
+   * a config sapling only for a namespace whose capabilities are listed by a
+   * manifest declaring that namespace. Defaults to none.
+   */
+  manifests?: ApprovedManifest[];
+}
+
+/**
+ * A working implementation of the `Canopy` interface, which also exposes the
+ * saplings registered with it. The host itself is unrestricted, and is only
+ * handed to the code embedding it.
+ */
+export interface CanopyHost extends Canopy {
+  /**
+   * The view of the host to install as `window.$CANOPY`. It enforces the
+   * capabilities of the mounted app, and exposes neither the host's records
+   * nor the functions that mount and unmount apps.
+   */
+  windowCanopy: Canopy;
+  /** Apps passed to `registerApp`, in registration order. */
+  apps: RegisteredApp[];
+  /** The app currently mounted, if any. */
+  mountedApp: RegisteredApp | null;
+  /** True while the mounted app is suspended. */
+  suspended: boolean;
+  /**
+   * The history apps are routed with. Navigating to the route prefix of
+   * another app mounts that app in place of the current one.
+   */
+  history: RouteHistory;
+  /** Config saplings passed to `registerConfigSapling`, in order. */
+  configSaplings: RegisteredConfigSapling[];
+  /**
+   * Runs the config saplings one after the other, in the order of their
+   * namespaces and then in registration order. Each is bootstrapped with the
+   * input of its namespace, keyed by namespace name, and what it resolves with
+   * is applied by its namespace.
+   */
+  runConfigSaplings(inputs?: {
+    [namespace: string]: unknown;
+  }): Promise<ConfigSaplingResult[]>;
+  /** Number of times `hideCanopy` has been called. */
+  hideCanopyCount: number;
+  /**
+   * Bootstraps the named app into the given DOM node, unmounting the app
+   * currently mounted. Without a name, mounts the app whose route prefix
+   * matches the current location, or else the most recently registered app.
+   * Throws if there is no such app.
+   */
+  mountApp(domNode: Node, name?: string): Node;
+  /**
+   * Unmounts the app currently mounted, calling its teardown function or
+   * `unmount` hook. The message handlers and route listeners it subscribed
+   * through its context are unsubscribed.
+   */
+  unmountApp(): void;
+  /** Calls the `suspend` hook of the mounted app, if it is not suspended. */
+  suspendApp(): void;
+  /** Calls the `resume` hook of the mounted app, if it is suspended. */
+  resumeApp(): void;
+}
+
+/**
+ * Builds a Canopy host that keeps the user and keys in memory, or in the
+ * given store. Shared by the mock Canopy used in tests and the development
+ * host.
+ */
+export function createCanopyHost({
+  sharedConfig,
+  user,
+  keys,
+  store,
+  history = memoryHistory(),
+  manifests = []
+}: CanopyHostOptions): CanopyHost {
+  const state: CanopyHostState = store ? store.load() : {};
+  if (user) {
+    state.user = user;
+  }
+  if (keys) {
+    state.keys = keys;
+  }
+
+  // Set with setKeySigner in place of the keys, and never saved to the store.
+  let keySigner: KeySigner | null = null;
+  // Set when the signer is removed, so that signing does not fall back to
+  // keys set earlier.
+  let keysLocked = false;
+
+  const currentKeys = (): KeyPair => {
+    if (keysLocked) {
+      throw new KeysLockedError('The keys are locked');
+    }
+    if (!state.keys) {
+      throw new Error('No keys have been set with setKeys');
+    }
+    return state.keys;
+  };
+
+  const save = (): void => {
+    if (store) {
+      store.save(state);
+    }
+  };
+
+  const userChanges = new Emitter<User | null>();
+  const keysChanges = new Emitter<KeyPair | null>();
+  const bus = new MessageBus();
+  const notifications = new NotificationCenter();
+  const apps: RegisteredApp[] = [];
+  let mountedNode: Node | null = null;
+  let lifecycle: SaplingLifecycle = {};
+  let subscriptions: Unsubscribe[] = [];
+
+  // Records a subscription made by the mounted app, so that it is released
+  // when the app is unmounted.
+  const track = (unsubscribe: Unsubscribe): Unsubscribe => {
+    subscriptions.push(unsubscribe);
+    return unsubscribe;
+  };
+
+  // Saplings that call the exported messaging functions reach the host rather
+  // than their context. While an app is mounted, those subscriptions belong to
+  // it; otherwise they belong to Canopy itself and are never released.
+  const hostMessaging = bus.scope();
+  const trackMounted = (unsubscribe: Unsubscribe): Unsubscribe =>
+    mountedNode ? track(unsubscribe) : unsubscribe;
+
+  // Returns the app with the longest route prefix matching the path.
+  const appForPath = (path: string): RegisteredApp | undefined =>
+    apps
+      .filter(app => matchesRoutePrefix(path, app.routePrefix))
+      .sort((a, b) => b.routePrefix.length - a.routePrefix.length)[0];
+
+  const findApp = (name?: string): RegisteredApp => {
+    const app = name
+      ? apps.filter(registered => registered.name === name)[0]
+      : appForPath(history.location()) || apps[apps.length - 1];
+    if (!app) {
+      throw new Error(
+        name
+          ? `No app named ${name} has been registered`
+          : 'No app has been registered with registerApp'
+      );
+    }
+    return app;
+  };
+
+  const configSaplings: RegisteredConfigSapling[] = [];
+  let mountedApp: RegisteredApp | null = null;
+
+  // Capabilities are granted by the approved manifests, never by the saplings
+  // registering themselves.
+  const checkGranted = (
+    saplingName: string,
+    requested: ReadonlyArray<Capability>,
+    approves: (manifest: ApprovedManifest) => boolean
+  ): void => {
+    const granted = manifests
+      .filter(approves)
+      .reduce(
+        (all, manifest) => all.concat(manifest.capabilities || []),
+        [] as Capability[]
+      );
+    requested.forEach(capability => {
+      if (!hasCapability(granted, capability)) {
+        throw new PermissionDeniedError(saplingName, capability);
+      }
+    });
+  };
+
+  // The unrestricted Canopy API. Saplings are only ever given views of it
+  // limited to their capabilities.
+  const api: Canopy = {
+    registerApp: (
+      bootstrapFunction,
+      { name, routePrefix, capabilities = [] } = {}
+    ) => {
+      const appName = name || `app${apps.length}`;
+      checkGranted(appName, capabilities, manifest =>
+        manifest.name === appName
+      );
+      apps.push(
+        Object.freeze({
+          name: appName,
+          routePrefix: normalizeRoutePrefix(routePrefix || appName),
+          capabilities: Object.freeze(capabilities.slice()),
+          bootstrapFunction
+        })
+      );
+    },
+    registerConfigSapling: (
+      configNamespace:
+        | ConfigNamespace<unknown, unknown>
+        | BuiltInConfigNamespace,
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      bootstrapFunction: ConfigSaplingBootstrap<any, any>
+    ) => {
+      const namespace = resolveConfigNamespace(configNamespace);
+      checkGranted(namespace.name, namespace.capabilities || [], manifest =>
+        (manifest.configNamespaces || []).includes(namespace.name)
+      );
+      configSaplings.push(
+        Object.freeze({
+          configNamespace: namespace,
+          bootstrapFunction,
+          typed: typeof configNamespace !== 'string'
+        })
+      );
+    },
+    getUser: () => state.user as User,
+    setUser: newUser => {
+      state.user = newUser || undefined;
+      save();
+      userChanges.emit(newUser);
+    },
+    getKeys: () => state.keys as KeyPair,
+    setKeys: newKeys => {
+      keySigner = null;
+      keysLocked = false;
+      state.keys = newKeys || undefined;
+      save();
+      keysChanges.emit(newKeys);
+    },
+    sign: async message =>
+      keySigner
+        ? keySigner.sign(message)
+        : createSigner(currentKeys().privateKey).sign(message),
+    getPublicKey: () =>
+      keySigner ? keySigner.getPublicKey() : currentKeys().publicKey,
+    setKeySigner: signer => {
+      keySigner = signer;
+      keysLocked = signer === null;
+      if (state.keys) {
+        state.keys = undefined;
+        save();
+        keysChanges.emit(null);
+      }
+    },
+    onUserChange: callback => userChanges.subscribe(callback),
+    onKeysChange: callback => keysChanges.subscribe(callback),
+    getSharedConfig: () => sharedConfig,
+    hideCanopy: () => {
+      // eslint-disable-next-line no-use-before-define, @typescript-eslint/no-use-before-define
+      host.hideCanopyCount += 1;
+    },
+    ...hostMessaging,
+    subscribe: (topic, handler) =>
+      trackMounted(hostMessaging.subscribe(topic, handler)),
+    reply: (topic, handler) =>
+      trackMounted(hostMessaging.reply(topic, handler)),
+    ...notifications.api()
+  };
+
+  const host: CanopyHost = {
+    ...api,
+    apps,
+    mountedApp: null,
+    suspended: false,
+    configSaplings,
+    hideCanopyCount: 0,
+    history,
+    // Saplings that call the exported functions reach this view rather than
+    // their context, so it enforces the mounted app's capabilities too. Calls
+    // made while no app is mounted come from Canopy and config saplings.
+    windowCanopy: guardCanopyForCaller(api, () =>
+      mountedApp
+        ? {
+            saplingName: mountedApp.name,
+            capabilities: mountedApp.capabilities
+          }
+        : null
+    ),
+    runConfigSaplings: (inputs = {}) =>
+      configSaplings
+        .map((sapling, index) => ({ sapling, index }))
+        .sort(
+          (a, b) =>
+            a.sapling.configNamespace.order - b.sapling.configNamespace.order ||
+            a.index - b.index
+        )
+        .reduce(
+          (previous, { sapling }) =>
+            previous.then(async results => {
+              const { configNamespace, bootstrapFunction, typed } = sapling;
+              const canopy = guardCanopy(
+                api,
+                configNamespace.name,
+                configNamespace.capabilities || []
+              );
+              const output = await bootstrapFunction(
+                inputs[configNamespace.name],
+                canopy
+              );
+              if (typed && configNamespace.apply) {
+                configNamespace.apply(output, canopy);
+              }
+              return results.concat({ configNamespace, output });
+            }),
+          Promise.resolve([] as ConfigSaplingResult[])
+        ),
+    mountApp: (domNode, name) => {
+      const app = findApp(name);
+      host.unmountApp();
+      mountedApp = app;
+      host.mountedApp = app;
+      mountedNode = domNode;
+      const router = createRouter(
+        app.routePrefix,
+        history,
+        saplingName => findApp(saplingName).routePrefix
+      );
+      const messaging = bus.scope(() => mountedApp === app);
+      lifecycle = toLifecycle(
+        app.bootstrapFunction(domNode, {
+          name: app.name,
+          canopy: guardCanopy(api, app.name, app.capabilities),
+          router: {
+            ...router,
+            onRouteChange: listener => track(router.onRouteChange(listener))
+          },
+          ...messaging,
+          subscribe: (topic, handler) =>
+            track(messaging.subscribe(topic, handler)),
+          reply: (topic, handler) => track(messaging.reply(topic, handler))
+        })
+      );
+      return domNode;
+    },
+    unmountApp: () => {
+      if (!mountedApp) {
+        return;
+      }
+      const { unmount } = lifecycle;
+      const released = subscriptions;
+      lifecycle = {};
+      subscriptions = [];
+      mountedApp = null;
+      host.mountedApp = null;
+      host.suspended = false;
+      mountedNode = null;
+      released.forEach(unsubscribe => unsubscribe());
+      if (unmount) {
+        unmount();
+      }
+    },
+    suspendApp: () => {
+      if (!mountedApp || host.suspended) {
+        return;
+      }
+      host.suspended = true;
+      if (lifecycle.suspend) {
+        lifecycle.suspend();
+      }
+    },
+    resumeApp: () => {
+      if (!mountedApp || !host.suspended) {
+        return;
+      }
+      host.suspended = false;
+      if (lifecycle.resume) {
+        lifecycle.resume();
+      }
+    }
+  };
+
+  history.listen(path => {
+    const app = appForPath(path);
+    if (mountedNode && app && app !== mountedApp) {
+      host.mountApp(mountedNode, app.name);
+    }
+  });
+  return host;
+}
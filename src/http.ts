@@ -0,0 +1,255 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  httpErrorFromResponse,
+  SplinterHttpError,
+  SplinterNetworkError,
+  SplinterParseError
+} from './errors';
+import { resolveToken, TokenProvider } from './auth';
+import {
+  abortError,
+  defaultTransport,
+  HttpHeaders,
+  Transport,
+  TransportResponse
+} from './transport';
+
+export interface RequestOptions {
+  /**
+   * Sends the Canopy user's token as a bearer token when `true`, or the token
+   * supplied by the given provider.
+   */
+  auth?: boolean | TokenProvider;
+  /** Sends the request through this transport instead of the default one. */
+  transport?: Transport;
+  /**
+   * Time to wait for a response, in milliseconds. Retries share it with the
+   * first attempt and stop once it has passed.
+   */
+  timeout?: number;
+  /** Cancels the request, including any pending retry. */
+  signal?: AbortSignal;
+  /** Retries failed requests with this policy, or never if `false`. */
+  retry?: RetryPolicy | false;
+}
+
+export interface RetryPolicy {
+  /** Number of times a failed request is retried. */
+  retries: number;
+  /** Delay before the first retry, in milliseconds. */
+  minDelay?: number;
+  /** Upper bound of the delay between retries, in milliseconds. */
+  maxDelay?: number;
+  /** Factor the delay grows by after each retry. */
+  factor?: number;
+}
+
+/**
+ * Retry policy used by requests that are safe to repeat, such as submitting
+ * batches, which the node deduplicates by batch ID.
+ */
+export const DEFAULT_RETRY_POLICY: RetryPolicy = {
+  retries: 3,
+  minDelay: 500,
+  maxDelay: 10000,
+  factor: 2
+};
+
+export interface HttpOptions extends RequestOptions {
+  headers?: HttpHeaders;
+}
+
+const HTTPMethods = ['GET', 'POST', 'PUT', 'PATCH', 'DELETE'];
+
+const RETRYABLE_STATUSES = [429, 502, 503, 504];
+
+function isRetryable(err: Error): boolean {
+  if (err instanceof SplinterNetworkError) {
+    return true;
+  }
+  return (
+    err instanceof SplinterHttpError && RETRYABLE_STATUSES.includes(err.status)
+  );
+}
+
+/**
+ * Reads the `Retry-After` header of a 429 or 503 response, which holds either
+ * a number of seconds or an HTTP date.
+ */
+function retryAfter(err: Error): number | null {
+  if (
+    !(err instanceof SplinterHttpError) ||
+    (err.status !== 429 && err.status !== 503) ||
+    !err.headers['retry-after']
+  ) {
+    return null;
+  }
+  const value = err.headers['retry-after'];
+  const seconds = Number(value);
+  if (!Number.isNaN(seconds)) {
+    return Math.max(0, seconds * 1000);
+  }
+  const date = Date.parse(value);
+  return Number.isNaN(date) ? null : Math.max(0, date - Date.now());
+}
+
+/**
+ * Computes the delay before the given retry: the `Retry-After` delay of the
+ * response if it has one, or else an exponential backoff with jitter so that
+ * clients do not retry in lockstep. Either is at most `maxDelay`.
+ */
+function retryDelay(
+  { minDelay = 500, maxDelay = 10000, factor = 2 }: RetryPolicy,
+  attempt: number,
+  err: Error
+): number {
+  const wait = retryAfter(err);
+  if (wait !== null) {
+    return Math.min(wait, maxDelay);
+  }
+  const ceiling = Math.min(maxDelay, minDelay * factor ** attempt);
+  return ceiling / 2 + Math.random() * (ceiling / 2);
+}
+
+/**
+ * Waits for the given time before a request is repeated, rejecting with a
+ * `SplinterAbortError` if the request is aborted in the meantime.
+ */
+export function delay(
+  ms: number,
+  signal: AbortSignal | undefined,
+  request: { method: string; url: string }
+): Promise<void> {
+  return new Promise((resolve, reject) => {
+    if (signal && signal.aborted) {
+      reject(abortError(request));
+      return;
+    }
+    const abort = (): void => {
+      // eslint-disable-next-line no-use-before-define, @typescript-eslint/no-use-before-define
+      clearTimeout(timer);
+      reject(abortError(request));
+    };
+    const timer = setTimeout(() => {
+      if (signal) {
+        signal.removeEventListener('abort', abort);
+      }
+      resolve();
+    }, ms);
+    if (signal) {
+      signal.addEventListener('abort', abort);
+    }
+  });
+}
+
+/**
+ * Sends a request to a Splinter node, rejecting with a `SplinterHttpError` if
+ * the response status is not successful.
+ * @param {string}      method    HTTP method for the request
+ * @param {string}      url       endpoint to make the request to
+ * @param {Uint8Array}  data      Byte array representation of the request body
+ * @param {object}      options   Headers, authentication, transport, timeout
+ *                                and retry options
+ */
+export async function http(
+  method: string,
+  url: string,
+  data: Uint8Array | string | null,
+  {
+    headers = {},
+    auth,
+    transport = defaultTransport(),
+    timeout,
+    signal,
+    retry = false
+  }: HttpOptions = {}
+): Promise<TransportResponse> {
+  if (!HTTPMethods.includes(method.toUpperCase())) {
+    throw Error('Invalid HTTP Method');
+  }
+
+  const token = await resolveToken(auth);
+  const requestHeaders = token
+    ? { ...headers, Authorization: `Bearer ${token}` }
+    : headers;
+
+  const deadline = timeout ? Date.now() + timeout : null;
+
+  const send = async (): Promise<TransportResponse> => {
+    const response = await transport({
+      method,
+      url,
+      headers: requestHeaders,
+      body: data,
+      timeout:
+        deadline === null ? undefined : Math.max(deadline - Date.now(), 1),
+      signal
+    });
+    if (response.status < 200 || response.status >= 300) {
+      throw httpErrorFromResponse(
+        {
+          method,
+          url,
+          status: response.status,
+          body: response.body,
+          headers: response.headers
+        },
+        !!token
+      );
+    }
+    return response;
+  };
+
+  const attempt = async (retries: number): Promise<TransportResponse> => {
+    try {
+      return await send();
+    } catch (err) {
+      if (!retry || retries >= retry.retries || !isRetryable(err)) {
+        throw err;
+      }
+      const wait = retryDelay(retry, retries, err);
+      if (deadline !== null && Date.now() + wait >= deadline) {
+        throw err;
+      }
+      await delay(wait, signal, { method, url });
+      return attempt(retries + 1);
+    }
+  };
+
+  return attempt(0);
+}
+
+/**
+ * Parses a successful response body, raising a `SplinterParseError` if it is
+ * not valid JSON.
+ */
+export function parseJSON(
+  method: string,
+  url: string,
+  { status, body }: TransportResponse
+): unknown {
+  try {
+    return JSON.parse(body);
+  } catch (err) {
+    throw new SplinterParseError(`Unable to parse response: ${err.message}`, {
+      method,
+      url,
+      status,
+      body
+    });
+  }
+}
@@ -13,95 +13,209 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-
-export { submitBatchList } from './submitter';
-export { decryptKey, encryptKey } from './crypto';
-
-interface User {
-  userId: string;
-  displayName?: string;
-  token?: string;
-}
-
-interface KeyPair {
-  publicKey: string;
-  privateKey: string;
-}
-
-interface SetUser {
-  (user: User): void;
-}
-
-interface SetKeys {
-  (keys: KeyPair): void;
-}
-
-interface SharedConfig {
-  canopyConfig: {
-    splinterURL: string;
-  };
-}
-
-interface GetSharedConfig {
-  (): SharedConfig;
-}
-
-interface GetUser {
-  (): User;
-}
-
-interface GetKeys {
-  (): KeyPair;
-}
-
-interface RegisterApp {
-  (bootstrapFunction: (domNode: Node) => void): void;
-}
-
-interface RegisterConfigSapling {
-  (
-    configNamespace: 'login' | 'notifications',
-    bootstrapFunction: () => void
-  ): void;
-}
-
-interface HideCanopy {
-  (): void;
-}
-
-interface Canopy {
-  registerApp: RegisterApp;
-  registerConfigSapling: RegisterConfigSapling;
-  getUser: GetUser;
-  setUser: SetUser;
-  setKeys: SetKeys;
-  getKeys: GetKeys;
-  getSharedConfig: GetSharedConfig;
-  hideCanopy: HideCanopy;
-}
-
-function assertAndGetWindowCanopy(): Canopy {
-  // In order to prevent the need to overwrite the window interface,
-  // a intentional `any` is cast here.
-  // eslint-disable-next-line @typescript-eslint/no-explicit-any
-  if (!window || !(window as any).$CANOPY) {
-    throw new Error(
-      `Must be in a Canopy with 'window.$CANOPY' in scope to call this CanopyJS functions`
-    );
-  }
-  // eslint-disable-next-line @typescript-eslint/no-explicit-any
-  return (window as any).$CANOPY;
-}
-
-const canopy = assertAndGetWindowCanopy();
-
-export const {
+export {
+  submitBatchList,
+  waitForBatches,
+  BatchInvalidError,
+  BatchTimeoutError,
+  BatchInfo,
+  BatchStatus,
+  BatchMessage,
+  WaitForBatchesOptions,
+  SubmitBatchListOptions
+} from './submitter';
+export { RequestOptions, RetryPolicy, DEFAULT_RETRY_POLICY } from './http';
+export {
+  Transport,
+  TransportRequest,
+  TransportResponse,
+  HttpHeaders,
+  xhrTransport,
+  fetchTransport
+} from './transport';
+export { TokenProvider, canopyTokenProvider } from './auth';
+export {
+  SplinterHttpError,
+  SplinterClientError,
+  SplinterSessionExpiredError,
+  SplinterServerError,
+  SplinterNetworkError,
+  SplinterTimeoutError,
+  SplinterAbortError,
+  SplinterParseError,
+  CircuitProposalError,
+  KeyDecryptionError,
+  CanopyUnavailableError,
+  SaplingMessageError,
+  ManifestError,
+  PermissionDeniedError,
+  KeysLockedError,
+  KeystoreError,
+  KeyFormatError
+} from './errors';
+export {
+  decryptKey,
+  encryptKey,
+  needsRehash,
+  KEY_ENCRYPTION_VERSION,
+  DEFAULT_PBKDF2_ITERATIONS,
+  KeyEncryptionOptions,
+  generateKeyPair,
+  publicKeyFromPrivate,
+  createSigner,
+  canopySigner,
+  verify,
+  sha256,
+  sha512,
+  Signer,
+  importPrivateKey,
+  importPrivateKeyHex,
+  importPublicKeyHex,
+  importPrivateKeyPem,
+  importPrivateKeyJwk,
+  exportPrivateKeyHex,
+  exportPublicKeyHex,
+  exportPrivateKeyPem,
+  exportPrivateKeyJwk,
+  KeyExportOptions,
+  PemExportOptions,
+  Secp256k1Jwk
+} from './crypto';
+export {
+  listCircuits,
+  getCircuit,
+  listProposals,
+  getProposal,
+  proposeCircuit,
+  voteOnProposal,
+  disbandCircuit,
+  purgeCircuit,
+  abandonCircuit,
+  encodeCircuit,
+  encodeCircuitCreateRequest,
+  Circuit,
+  CircuitService,
+  CircuitMember,
+  CircuitProposal,
+  ProposedCircuit,
+  ProposalVote,
+  AuthorizationType,
+  VoteType,
+  Page,
+  Paging,
+  AdminOptions,
+  ListOptions,
+  SubmitOptions
+} from './admin';
+export { CircuitProposalBuilder } from './proposal';
+export {
+  buildBatchList,
+  sabreTransaction,
+  namespaceRegistryAddress,
+  contractRegistryAddress,
+  contractAddress,
+  TransactionParams,
+  SabreTransactionParams,
+  BatchOptions,
+  BuiltBatchList
+} from './batch';
+export {
   registerApp,
   registerConfigSapling,
   getUser,
   setUser,
   setKeys,
   getKeys,
+  sign,
+  getPublicKey,
+  setKeySigner,
   getSharedConfig,
-  hideCanopy
-}: Canopy = canopy;
+  hideCanopy,
+  onUserChange,
+  onKeysChange,
+  publish,
+  subscribe,
+  request,
+  reply,
+  notify,
+  getNotifications,
+  markNotificationRead,
+  dismissNotification,
+  onNotificationsChange,
+  isInCanopy,
+  User,
+  KeyPair,
+  KeySigner,
+  SharedConfig,
+  Canopy,
+  UserChangeListener,
+  KeysChangeListener,
+  AppBootstrap,
+  AppOptions,
+  SaplingContext,
+  SaplingLifecycle,
+  Teardown,
+  ConfigNamespace,
+  ConfigSaplingBootstrap,
+  BuiltInConfigNamespace,
+  Capability
+} from './canopy';
+export {
+  CAPABILITIES,
+  CanopyCaller,
+  guardCanopy,
+  guardCanopyForCaller,
+  hasCapability
+} from './permissions';
+export {
+  KeySession,
+  requestUnlock,
+  KeyLockReason,
+  KeySessionOptions,
+  KeySessionCanopy,
+  KEYS_LOCKED_TOPIC,
+  UNLOCK_KEYS_TOPIC,
+  DEFAULT_IDLE_TIMEOUT
+} from './keySession';
+export {
+  Keystore,
+  KeystoreOptions,
+  KeystoreBackend,
+  KeystoreData,
+  StoredKey,
+  DEFAULT_KEYSTORE_NAME,
+  defaultKeystoreBackend,
+  indexedDBBackend,
+  localStorageBackend,
+  memoryBackend
+} from './keystore';
+export {
+  defineConfigNamespace,
+  ConfigNamespaceOptions,
+  LoginResult,
+  LOGIN_NAMESPACE,
+  NOTIFICATIONS_NAMESPACE
+} from './configNamespaces';
+export { Unsubscribe } from './events';
+export {
+  defineTopic,
+  Topic,
+  SaplingMessaging,
+  MessageRequestOptions
+} from './messaging';
+export { SaplingRouter } from './routing';
+export {
+  NotificationLevel,
+  NotificationAction,
+  NotificationOptions,
+  SaplingNotification
+} from './notifications';
+export {
+  loadManifest,
+  parseManifest,
+  validateManifest,
+  isCompatibleManifest,
+  CANOPY_API_VERSION,
+  SaplingManifest,
+  LoadManifestOptions
+} from './manifest';
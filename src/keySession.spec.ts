@@ -0,0 +1,161 @@
+/**
+ * @jest-environment node
+ */
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { LOGIN_NAMESPACE } from './configNamespaces';
+import { encryptKey, publicKeyFromPrivate, verify } from './crypto';
+import { KeysLockedError } from './errors';
+import { createCanopyHost } from './host';
+import {
+  KeySession,
+  KEYS_LOCKED_TOPIC,
+  UNLOCK_KEYS_TOPIC
+} from './keySession';
+import { MessageBus } from './messaging';
+
+const privateKey = 'a'.repeat(64);
+const publicKey = publicKeyFromPrivate(privateKey);
+const message = new Uint8Array([1, 2, 3]);
+
+describe('KeySession', () => {
+  let encrypted: string;
+  let bus: MessageBus;
+  let setKeySigner: jest.Mock;
+
+  function createSession(idleTimeout = 1000, maxLifetime = 0): KeySession {
+    return new KeySession({
+      idleTimeout,
+      maxLifetime,
+      canopy: { ...bus.scope(), setKeySigner }
+    });
+  }
+
+  beforeAll(async () => {
+    encrypted = await encryptKey(privateKey, 'password', { iterations: 1000 });
+  });
+
+  beforeEach(() => {
+    jest.useFakeTimers();
+    bus = new MessageBus();
+    setKeySigner = jest.fn();
+  });
+
+  afterEach(() => {
+    jest.useRealTimers();
+  });
+
+  it('should sign for Canopy without revealing the private key', async () => {
+    const session = createSession();
+    await expect(session.unlock(encrypted, 'password')).resolves.toEqual(
+      publicKey
+    );
+    expect(setKeySigner).toHaveBeenCalledWith(session);
+    const signature = await session.sign(message);
+    expect(verify(message, signature, session.getPublicKey())).toBe(true);
+  });
+
+  it('should lock after the idle timeout and report it', async () => {
+    const onLocked = jest.fn();
+    const published = jest.fn();
+    bus.scope().subscribe(KEYS_LOCKED_TOPIC, published);
+    const session = createSession(1000);
+    session.onLocked(onLocked);
+    await session.unlock(encrypted, 'password');
+
+    jest.advanceTimersByTime(600);
+    session.touch();
+    jest.advanceTimersByTime(600);
+    expect(session.isLocked()).toBe(false);
+
+    jest.advanceTimersByTime(400);
+    expect(session.isLocked()).toBe(true);
+    expect(onLocked).toHaveBeenCalledWith('idle');
+    expect(published).toHaveBeenCalledWith('idle');
+    expect(setKeySigner).toHaveBeenLastCalledWith(null);
+    await expect(session.sign(message)).rejects.toBeInstanceOf(
+      KeysLockedError
+    );
+  });
+
+  it('should lock after the maximum lifetime however active', async () => {
+    const onLocked = jest.fn();
+    const session = createSession(1000, 1500);
+    session.onLocked(onLocked);
+    await session.unlock(encrypted, 'password');
+    jest.advanceTimersByTime(900);
+    session.touch();
+    jest.advanceTimersByTime(600);
+    expect(onLocked).toHaveBeenCalledWith('expired');
+  });
+
+  it('should zero the key bytes when locked', async () => {
+    const session = createSession();
+    await session.unlock(encrypted, 'password');
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    const keyBytes: Uint8Array = (session as any).privateKey;
+    expect(keyBytes.some(byte => byte !== 0)).toBe(true);
+    session.lock();
+    expect(keyBytes.every(byte => byte === 0)).toBe(true);
+    expect(() => session.getPublicKey()).toThrow(KeysLockedError);
+  });
+
+  it('should unlock again when another sapling requests it', async () => {
+    const session = createSession();
+    const promptPassword = jest.fn(async () => 'password');
+    session.serveUnlockRequests(promptPassword);
+    await session.unlock(encrypted, 'password');
+    session.lock();
+
+    await bus.scope().request(UNLOCK_KEYS_TOPIC, undefined);
+    expect(promptPassword).toHaveBeenCalledTimes(1);
+    expect(session.isLocked()).toBe(false);
+  });
+
+  it('should lock while an app without keys:write is mounted', async () => {
+    const host = createCanopyHost({
+      sharedConfig: { canopyConfig: { splinterURL: 'http://localhost:8080' } },
+      manifests: [
+        {
+          name: 'login',
+          configNamespaces: ['login'],
+          capabilities: ['user:write', 'keys:write']
+        }
+      ]
+    });
+    const sessions: KeySession[] = [];
+    host.registerConfigSapling(LOGIN_NAMESPACE, async (input, canopy) => {
+      const session = new KeySession({ idleTimeout: 1000, canopy });
+      sessions.push(session);
+      await session.unlock(encrypted, 'password');
+      return { user: { userId: 'alice' } };
+    });
+    await host.runConfigSaplings();
+    host.registerApp(jest.fn(), { name: 'circuits' });
+    host.mountApp({} as Node);
+
+    const onLocked = jest.fn();
+    const published = jest.fn();
+    sessions[0].onLocked(onLocked);
+    host.subscribe(KEYS_LOCKED_TOPIC, published);
+    expect(host.getPublicKey()).toEqual(publicKey);
+
+    jest.advanceTimersByTime(1000);
+    expect(onLocked).toHaveBeenCalledWith('idle');
+    expect(published).toHaveBeenCalledWith('idle');
+    expect(() => host.getPublicKey()).toThrow(KeysLockedError);
+  });
+});
@@ -0,0 +1,293 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  Canopy,
+  isInCanopy,
+  KeySigner,
+  publish,
+  reply,
+  request,
+  setKeySigner
+} from './canopy';
+import { createSigner, decryptKey, publicKeyFromPrivate } from './crypto';
+import { bytesToHex, hexToBytes } from './encoding';
+import { KeysLockedError } from './errors';
+import { Emitter, Unsubscribe } from './events';
+import { defineTopic } from './messaging';
+
+/**
+ * Why a key session was locked: the idle timeout or the maximum lifetime
+ * expired, or `lock` was called.
+ */
+export type KeyLockReason = 'idle' | 'expired' | 'manual';
+
+/** Published to every sapling when a key session is locked. */
+export const KEYS_LOCKED_TOPIC = defineTopic<KeyLockReason>('keys', 'locked');
+
+/** Requests sent by `requestUnlock`, answered once the keys are unlocked. */
+export const UNLOCK_KEYS_TOPIC = defineTopic<void, void>('keys', 'unlock');
+
+export const DEFAULT_IDLE_TIMEOUT = 15 * 60 * 1000;
+export const DEFAULT_UNLOCK_TIMEOUT = 5 * 60 * 1000;
+export const DEFAULT_ACTIVITY_EVENTS = ['mousedown', 'keydown', 'touchstart'];
+
+/**
+ * The Canopy functions a key session signs for Canopy with and reports its
+ * state through.
+ */
+export type KeySessionCanopy = Pick<
+  Canopy,
+  'setKeySigner' | 'publish' | 'reply'
+>;
+
+export interface KeySessionOptions {
+  /**
+   * Locks the keys after this many milliseconds without activity, or never
+   * if 0. Defaults to 15 minutes.
+   */
+  idleTimeout?: number;
+  /**
+   * Locks the keys this many milliseconds after they were unlocked, however
+   * active the user is, or never if 0. Defaults to never.
+   */
+  maxLifetime?: number;
+  /**
+   * DOM events on `document` that count as activity. Signing with the session
+   * and calling `touch` always count.
+   */
+  activityEvents?: string[];
+  /**
+   * Defaults to the Canopy in scope, if any, whose calls are checked against
+   * the capabilities of the mounted app. Login saplings should pass the
+   * Canopy their bootstrap function is given instead. Pass `null` to keep the
+   * keys out of Canopy.
+   */
+  canopy?: KeySessionCanopy | null;
+}
+
+/**
+ * Asks the sapling serving unlock requests, usually the login sapling, to
+ * prompt the user for their password. Resolves once the keys are unlocked.
+ * @param timeout - Time to wait for the user, in milliseconds.
+ */
+export function requestUnlock(timeout = DEFAULT_UNLOCK_TIMEOUT): Promise<void> {
+  return request(UNLOCK_KEYS_TOPIC, undefined, { timeout });
+}
+
+/**
+ * Holds a decrypted private key for a limited time. While unlocked, the
+ * session serves Canopy's `sign` and `getPublicKey` through `setKeySigner`,
+ * so the private key never leaves it. Once the idle timeout or the maximum
+ * lifetime expires, the key bytes are zeroed, the session stops signing for
+ * Canopy and a "locked" event is fired.
+ *
+ * JavaScript strings cannot be zeroed, so the hex strings used while
+ * decrypting and signing are only released for garbage collection.
+ */
+export class KeySession implements KeySigner {
+  private privateKey: Uint8Array | null = null;
+
+  private publicKey: string | null = null;
+
+  private encryptedPrivateKey: string | null = null;
+
+  private idleTimer: ReturnType<typeof setTimeout> | null = null;
+
+  private lifetimeTimer: ReturnType<typeof setTimeout> | null = null;
+
+  private removeActivityListeners: Unsubscribe | null = null;
+
+  private locked = new Emitter<KeyLockReason>();
+
+  private idleTimeout: number;
+
+  private maxLifetime: number;
+
+  private activityEvents: string[];
+
+  private canopy: KeySessionCanopy | null;
+
+  constructor({
+    idleTimeout = DEFAULT_IDLE_TIMEOUT,
+    maxLifetime = 0,
+    activityEvents = DEFAULT_ACTIVITY_EVENTS,
+    canopy = isInCanopy() ? { setKeySigner, publish, reply } : null
+  }: KeySessionOptions = {}) {
+    this.idleTimeout = idleTimeout;
+    this.maxLifetime = maxLifetime;
+    this.activityEvents = activityEvents;
+    this.canopy = canopy;
+  }
+
+  /**
+   * Decrypts the private key and holds it until the session locks. The
+   * encrypted key is kept so that the session can be unlocked again with
+   * just the password.
+   * @returns the public key
+   * @throws {KeyDecryptionError} if the password is wrong or the key corrupt
+   */
+  async unlock(encryptedPrivateKey: string, password: string): Promise<string> {
+    const privateKey = await decryptKey(encryptedPrivateKey, password);
+    const publicKey = publicKeyFromPrivate(privateKey);
+    this.release();
+    this.encryptedPrivateKey = encryptedPrivateKey;
+    this.privateKey = hexToBytes(privateKey);
+    this.publicKey = publicKey;
+
+    this.touch();
+    if (this.maxLifetime > 0) {
+      this.lifetimeTimer = setTimeout(
+        () => this.lock('expired'),
+        this.maxLifetime
+      );
+    }
+    this.listenForActivity();
+    if (this.canopy) {
+      this.canopy.setKeySigner(this);
+    }
+    return publicKey;
+  }
+
+  /**
+   * Zeroes the private key and locks the keys in Canopy.
+   * @param reason - Reported to the "locked" listeners.
+   */
+  lock(reason: KeyLockReason = 'manual'): void {
+    if (this.isLocked()) {
+      return;
+    }
+    this.release();
+    try {
+      if (this.canopy) {
+        this.canopy.setKeySigner(null);
+      }
+    } finally {
+      this.locked.emit(reason);
+      if (this.canopy) {
+        this.canopy.publish(KEYS_LOCKED_TOPIC, reason);
+      }
+    }
+  }
+
+  isLocked(): boolean {
+    return this.privateKey === null;
+  }
+
+  /**
+   * Records activity, restarting the idle timeout.
+   */
+  touch(): void {
+    if (!this.privateKey || this.idleTimeout <= 0) {
+      return;
+    }
+    if (this.idleTimer) {
+      clearTimeout(this.idleTimer);
+    }
+    this.idleTimer = setTimeout(() => this.lock('idle'), this.idleTimeout);
+  }
+
+  /**
+   * @throws {KeysLockedError} if the session is locked
+   */
+  getPublicKey(): string {
+    if (!this.publicKey) {
+      throw new KeysLockedError('The keys are locked');
+    }
+    return this.publicKey;
+  }
+
+  /**
+   * Signs a message with the held key, which counts as activity.
+   * @throws {KeysLockedError} if the session is locked
+   */
+  async sign(message: Uint8Array): Promise<string> {
+    if (!this.privateKey) {
+      throw new KeysLockedError('The keys are locked');
+    }
+    this.touch();
+    return createSigner(bytesToHex(this.privateKey)).sign(message);
+  }
+
+  /**
+   * Calls the listener with the reason each time the session locks.
+   */
+  onLocked(listener: (reason: KeyLockReason) => void): Unsubscribe {
+    return this.locked.subscribe(listener);
+  }
+
+  /**
+   * Answers `requestUnlock` calls from other saplings by prompting the user
+   * for their password and unlocking the key last unlocked. Called by the
+   * login sapling.
+   *
+   * @example
+   * session.serveUnlockRequests(() => showPasswordDialog());
+   */
+  serveUnlockRequests(promptPassword: () => Promise<string>): Unsubscribe {
+    if (!this.canopy) {
+      throw new Error('Unlock requests can only be served in a Canopy');
+    }
+    return this.canopy.reply(UNLOCK_KEYS_TOPIC, async () => {
+      if (!this.isLocked()) {
+        return;
+      }
+      if (!this.encryptedPrivateKey) {
+        throw new KeysLockedError('No key has been unlocked in this session');
+      }
+      const password = await promptPassword();
+      await this.unlock(this.encryptedPrivateKey, password);
+    });
+  }
+
+  // Zeroes the key and stops watching for activity.
+  private release(): void {
+    if (this.privateKey) {
+      this.privateKey.fill(0);
+    }
+    this.privateKey = null;
+    this.publicKey = null;
+    this.clearTimers();
+    if (this.removeActivityListeners) {
+      this.removeActivityListeners();
+      this.removeActivityListeners = null;
+    }
+  }
+
+  private clearTimers(): void {
+    if (this.idleTimer) {
+      clearTimeout(this.idleTimer);
+      this.idleTimer = null;
+    }
+    if (this.lifetimeTimer) {
+      clearTimeout(this.lifetimeTimer);
+      this.lifetimeTimer = null;
+    }
+  }
+
+  private listenForActivity(): void {
+    if (typeof document === 'undefined' || this.activityEvents.length === 0) {
+      return;
+    }
+    const onActivity = (): void => this.touch();
+    this.activityEvents.forEach(event =>
+      document.addEventListener(event, onActivity, true)
+    );
+    this.removeActivityListeners = () =>
+      this.activityEvents.forEach(event =>
+        document.removeEventListener(event, onActivity, true)
+      );
+  }
+}
@@ -0,0 +1,250 @@
+/**
+ * @jest-environment node
+ */
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { decryptKey, publicKeyFromPrivate } from './crypto';
+import { KeyDecryptionError, KeystoreError } from './errors';
+import {
+  indexedDBBackend,
+  Keystore,
+  KeystoreData,
+  localStorageBackend,
+  memoryBackend
+} from './keystore';
+
+const privateKey = 'a'.repeat(64);
+const publicKey = publicKeyFromPrivate(privateKey);
+const otherPrivateKey = 'b'.repeat(64);
+const otherPublicKey = publicKeyFromPrivate(otherPrivateKey);
+const options = { iterations: 1000 };
+
+interface FakeRequest {
+  result?: unknown;
+  onsuccess?: () => void;
+  onupgradeneeded?: () => void;
+}
+
+interface FakeTransaction {
+  error: Error | null;
+  oncomplete?: () => void;
+  onabort?: () => void;
+  objectStore(): {
+    get(key: string): FakeRequest;
+    put(value: unknown, key: string): FakeRequest;
+  };
+}
+
+type Records = { [key: string]: unknown };
+
+// A minimal IndexedDB with a single object store. Each transaction completes,
+// or aborts if `abortWrites` is set, after its request has succeeded.
+function fakeIndexedDB(abortWrites = false): { open(): FakeRequest } {
+  const records: Records = {};
+  const later = (callback: () => void): void => {
+    Promise.resolve().then(callback);
+  };
+
+  const finish = (tx: FakeTransaction, mode: string, writes: Records): void => {
+    if (mode === 'readwrite' && abortWrites) {
+      tx.error = new Error('Quota exceeded');
+      if (tx.onabort) {
+        tx.onabort();
+      }
+      return;
+    }
+    Object.assign(records, writes);
+    if (tx.oncomplete) {
+      tx.oncomplete();
+    }
+  };
+
+  const respond = (result: unknown, done: () => void): FakeRequest => {
+    const request: FakeRequest = {};
+    later(() => {
+      request.result = result;
+      if (request.onsuccess) {
+        request.onsuccess();
+      }
+      later(done);
+    });
+    return request;
+  };
+
+  const transaction = (storeName: string, mode: string): FakeTransaction => {
+    const writes: Records = {};
+    const tx: FakeTransaction = {
+      error: null,
+      objectStore: () => ({
+        get: key => respond(records[key], () => finish(tx, mode, writes)),
+        put: (value, key) => {
+          writes[key] = value;
+          return respond(key, () => finish(tx, mode, writes));
+        }
+      })
+    };
+    return tx;
+  };
+
+  return {
+    open: () => {
+      const request: FakeRequest = {};
+      later(() => {
+        request.result = { createObjectStore: jest.fn(), transaction };
+        if (request.onupgradeneeded) {
+          request.onupgradeneeded();
+        }
+        if (request.onsuccess) {
+          request.onsuccess();
+        }
+      });
+      return request;
+    }
+  };
+}
+
+describe('Keystore', () => {
+  let setKeys: jest.Mock;
+  let keystore: Keystore;
+
+  beforeEach(async () => {
+    setKeys = jest.fn();
+    keystore = await Keystore.open({
+      backend: memoryBackend(),
+      canopy: { setKeys }
+    });
+  });
+
+  it('should store keys encrypted with the password', async () => {
+    const stored = await keystore.add('Admin', privateKey, 'secret', options);
+    expect(stored).toMatchObject({ publicKey, label: 'Admin' });
+    expect(stored.encryptedPrivateKey).not.toContain(privateKey);
+    await expect(
+      decryptKey(stored.encryptedPrivateKey, 'secret')
+    ).resolves.toEqual(privateKey);
+    expect(keystore.list()).toEqual([stored]);
+  });
+
+  it('should reject duplicate keys and labels', async () => {
+    await keystore.add('Admin', privateKey, 'secret', options);
+    await expect(
+      keystore.add('Other', privateKey, 'secret', options)
+    ).rejects.toBeInstanceOf(KeystoreError);
+    await expect(
+      keystore.add('Admin', otherPrivateKey, 'secret', options)
+    ).rejects.toBeInstanceOf(KeystoreError);
+    expect(keystore.list()).toHaveLength(1);
+  });
+
+  it('should reject a key added twice at once', async () => {
+    const results = await Promise.all([
+      keystore.add('Admin', privateKey, 'secret', options).catch(err => err),
+      keystore.add('Other', privateKey, 'secret', options).catch(err => err)
+    ]);
+    expect(results[1]).toBeInstanceOf(KeystoreError);
+    expect(keystore.list()).toEqual([results[0]]);
+  });
+
+  it('should rename and remove keys', async () => {
+    await keystore.add('Admin', privateKey, 'secret', options);
+    await keystore.add('Member', otherPrivateKey, 'secret', options);
+    await keystore.rename(publicKey, 'Operator');
+    expect(keystore.list().map(key => key.label)).toEqual([
+      'Operator',
+      'Member'
+    ]);
+    await keystore.remove(otherPublicKey);
+    expect(keystore.list().map(key => key.publicKey)).toEqual([publicKey]);
+    await expect(keystore.remove(otherPublicKey)).rejects.toBeInstanceOf(
+      KeystoreError
+    );
+  });
+
+  it('should set the active key in Canopy', async () => {
+    await keystore.add('Admin', privateKey, 'secret', options);
+    await expect(keystore.activate(publicKey, 'secret')).resolves.toEqual({
+      publicKey,
+      privateKey
+    });
+    expect(setKeys).toHaveBeenCalledWith({ publicKey, privateKey });
+    expect(keystore.getActiveKey()).toMatchObject({ label: 'Admin' });
+
+    await keystore.remove(publicKey);
+    expect(setKeys).toHaveBeenLastCalledWith(null);
+    expect(keystore.getActiveKey()).toBeNull();
+  });
+
+  it('should not activate a key with the wrong password', async () => {
+    await keystore.add('Admin', privateKey, 'secret', options);
+    await expect(
+      keystore.activate(publicKey, 'wrong')
+    ).rejects.toBeInstanceOf(KeyDecryptionError);
+    expect(setKeys).not.toHaveBeenCalled();
+    expect(keystore.getActiveKey()).toBeNull();
+  });
+
+  it('should persist keys to local storage', async () => {
+    const items: { [key: string]: string } = {};
+    const storage = ({
+      getItem: (key: string) => (key in items ? items[key] : null),
+      setItem: (key: string, value: string) => {
+        items[key] = value;
+      }
+    } as unknown) as Storage;
+    const first = await Keystore.open({
+      backend: localStorageBackend(storage),
+      canopy: null
+    });
+    await first.add('Admin', privateKey, 'secret', options);
+    await first.activate(publicKey, 'secret');
+
+    const reopened = await Keystore.open({
+      backend: localStorageBackend(storage),
+      canopy: null
+    });
+    expect(reopened.list()).toEqual(first.list());
+    expect(reopened.getActiveKey()).toEqual(first.getActiveKey());
+  });
+});
+
+describe('indexedDBBackend(databaseName)', () => {
+  const data: KeystoreData = { keys: [], activePublicKey: null };
+
+  function install(abortWrites?: boolean): void {
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    (global as any).indexedDB = fakeIndexedDB(abortWrites);
+  }
+
+  afterEach(() => {
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    delete (global as any).indexedDB;
+  });
+
+  it('should load what was saved', async () => {
+    install();
+    const backend = indexedDBBackend();
+    await expect(backend.load()).resolves.toBeNull();
+    await backend.save(data);
+    await expect(backend.load()).resolves.toEqual(data);
+  });
+
+  it('should reject when the transaction aborts', async () => {
+    install(true);
+    const backend = indexedDBBackend();
+    await expect(backend.save(data)).rejects.toThrow('Quota exceeded');
+    await expect(backend.load()).resolves.toBeNull();
+  });
+});
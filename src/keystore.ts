@@ -0,0 +1,346 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { Canopy, isInCanopy, KeyPair, setKeys } from './canopy';
+import {
+  decryptKey,
+  encryptKey,
+  KeyEncryptionOptions,
+  publicKeyFromPrivate
+} from './crypto';
+import { KeystoreError } from './errors';
+
+/**
+ * A key held by a keystore. The private key is only stored encrypted.
+ */
+export interface StoredKey {
+  /** Identifies the key within the keystore. */
+  publicKey: string;
+  label: string;
+  /** The private key, encrypted with `encryptKey`. */
+  encryptedPrivateKey: string;
+  /** When the key was added, in milliseconds since the epoch. */
+  createdAt: number;
+}
+
+export interface KeystoreData {
+  keys: StoredKey[];
+  activePublicKey: string | null;
+}
+
+/**
+ * Persists the contents of a keystore.
+ */
+export interface KeystoreBackend {
+  load(): Promise<KeystoreData | null>;
+  save(data: KeystoreData): Promise<void>;
+}
+
+export const DEFAULT_KEYSTORE_NAME = 'saplingjs.keystore';
+
+/**
+ * Keeps the keystore in memory, for tests.
+ */
+export function memoryBackend(): KeystoreBackend {
+  let stored: string | null = null;
+  return {
+    load: async () => (stored ? JSON.parse(stored) : null),
+    save: async data => {
+      stored = JSON.stringify(data);
+    }
+  };
+}
+
+/**
+ * Stores the keystore as JSON in local storage.
+ */
+export function localStorageBackend(
+  storage: Storage = window.localStorage,
+  storageKey = DEFAULT_KEYSTORE_NAME
+): KeystoreBackend {
+  return {
+    load: async () => {
+      const stored = storage.getItem(storageKey);
+      return stored ? JSON.parse(stored) : null;
+    },
+    save: async data => {
+      storage.setItem(storageKey, JSON.stringify(data));
+    }
+  };
+}
+
+function requestResult<T>(request: IDBRequest<T>): Promise<T> {
+  return new Promise((resolve, reject) => {
+    request.onsuccess = () => resolve(request.result);
+    request.onerror = () => reject(request.error);
+  });
+}
+
+// Writes are only durable once their transaction completes, which can still
+// abort after every request in it has succeeded.
+function transactionResult(transaction: IDBTransaction): Promise<void> {
+  return new Promise((resolve, reject) => {
+    const fail = (): void =>
+      reject(
+        transaction.error || new KeystoreError('Unable to save the keystore')
+      );
+    transaction.oncomplete = () => resolve();
+    transaction.onabort = fail;
+    transaction.onerror = fail;
+  });
+}
+
+/**
+ * Stores the keystore in an IndexedDB database holding a single object
+ * store.
+ */
+export function indexedDBBackend(
+  databaseName = DEFAULT_KEYSTORE_NAME
+): KeystoreBackend {
+  const storeName = 'keystore';
+  const recordKey = 'keystore';
+  let database: Promise<IDBDatabase> | null = null;
+
+  const open = (): Promise<IDBDatabase> => {
+    if (!database) {
+      const request = indexedDB.open(databaseName, 1);
+      request.onupgradeneeded = () => {
+        request.result.createObjectStore(storeName);
+      };
+      database = requestResult(request);
+    }
+    return database;
+  };
+
+  return {
+    load: async () => {
+      const db = await open();
+      const record = await requestResult(
+        db
+          .transaction(storeName, 'readonly')
+          .objectStore(storeName)
+          .get(recordKey)
+      );
+      return record || null;
+    },
+    save: async data => {
+      const db = await open();
+      const transaction = db.transaction(storeName, 'readwrite');
+      transaction.objectStore(storeName).put(data, recordKey);
+      await transactionResult(transaction);
+    }
+  };
+}
+
+/**
+ * Stores keys in IndexedDB where it is available, and in local storage
+ * otherwise.
+ */
+export function defaultKeystoreBackend(): KeystoreBackend {
+  return typeof indexedDB !== 'undefined'
+    ? indexedDBBackend()
+    : localStorageBackend();
+}
+
+export interface KeystoreOptions {
+  /** Defaults to IndexedDB, or local storage where it is not available. */
+  backend?: KeystoreBackend;
+  /**
+   * Receives the active key through `setKeys`. Defaults to the Canopy in
+   * scope, if any.
+   */
+  canopy?: Pick<Canopy, 'setKeys'> | null;
+}
+
+/**
+ * Holds many labeled keys, each encrypted with `encryptKey`, one of which
+ * can be active. Activating a key decrypts it and sets it in Canopy with
+ * `setKeys`.
+ *
+ * @example
+ * const keystore = await Keystore.open();
+ * await keystore.add('Organization admin', privateKey, password);
+ * await keystore.activate(publicKey, password);
+ */
+export class Keystore {
+  private data: KeystoreData;
+
+  private backend: KeystoreBackend;
+
+  private canopy: Pick<Canopy, 'setKeys'> | null;
+
+  private updates: Promise<void> = Promise.resolve();
+
+  private constructor(
+    data: KeystoreData,
+    backend: KeystoreBackend,
+    canopy: Pick<Canopy, 'setKeys'> | null
+  ) {
+    this.data = data;
+    this.backend = backend;
+    this.canopy = canopy;
+  }
+
+  /**
+   * Opens the keystore persisted in the backend, or an empty one.
+   */
+  static async open({
+    backend = defaultKeystoreBackend(),
+    canopy = isInCanopy() ? { setKeys } : null
+  }: KeystoreOptions = {}): Promise<Keystore> {
+    const data = (await backend.load()) || { keys: [], activePublicKey: null };
+    return new Keystore(data, backend, canopy);
+  }
+
+  /**
+   * Returns every key, in the order they were added.
+   */
+  list(): StoredKey[] {
+    return this.data.keys.map(key => ({ ...key }));
+  }
+
+  get(publicKey: string): StoredKey | null {
+    const key = this.data.keys.filter(
+      stored => stored.publicKey === publicKey
+    )[0];
+    return key ? { ...key } : null;
+  }
+
+  getActiveKey(): StoredKey | null {
+    return this.data.activePublicKey
+      ? this.get(this.data.activePublicKey)
+      : null;
+  }
+
+  /**
+   * Encrypts a private key with the password and adds it.
+   * @throws {KeystoreError} if the key or the label is already in use
+   */
+  async add(
+    label: string,
+    privateKey: string,
+    password: string,
+    options?: KeyEncryptionOptions
+  ): Promise<StoredKey> {
+    const publicKey = publicKeyFromPrivate(privateKey);
+    this.checkNewKey(publicKey, label);
+    const encryptedPrivateKey = await encryptKey(privateKey, password, options);
+    const key: StoredKey = {
+      publicKey,
+      label,
+      encryptedPrivateKey,
+      createdAt: Date.now()
+    };
+    // Another key may have been added while this one was being encrypted.
+    await this.update(data => {
+      this.checkNewKey(publicKey, label);
+      return { ...data, keys: [...data.keys, key] };
+    });
+    return { ...key };
+  }
+
+  /**
+   * Removes a key. Removing the active key clears the keys set in Canopy.
+   * @throws {KeystoreError} if there is no such key
+   */
+  async remove(publicKey: string): Promise<void> {
+    let wasActive = false;
+    await this.update(data => {
+      this.find(publicKey);
+      wasActive = data.activePublicKey === publicKey;
+      return {
+        keys: data.keys.filter(key => key.publicKey !== publicKey),
+        activePublicKey: wasActive ? null : data.activePublicKey
+      };
+    });
+    if (wasActive && this.canopy) {
+      this.canopy.setKeys(null);
+    }
+  }
+
+  /**
+   * @throws {KeystoreError} if there is no such key or the label is in use
+   */
+  async rename(publicKey: string, label: string): Promise<void> {
+    await this.update(data => {
+      this.find(publicKey);
+      this.checkLabel(label, publicKey);
+      return {
+        ...data,
+        keys: data.keys.map(key =>
+          key.publicKey === publicKey ? { ...key, label } : key
+        )
+      };
+    });
+  }
+
+  /**
+   * Decrypts a key, marks it as active and sets it in Canopy.
+   * @throws {KeystoreError} if there is no such key
+   * @throws {KeyDecryptionError} if the password is wrong
+   */
+  async activate(publicKey: string, password: string): Promise<KeyPair> {
+    const key = this.find(publicKey);
+    const privateKey = await decryptKey(key.encryptedPrivateKey, password);
+    await this.update(data => {
+      this.find(publicKey);
+      return { ...data, activePublicKey: publicKey };
+    });
+    const keys = { publicKey, privateKey };
+    if (this.canopy) {
+      this.canopy.setKeys(keys);
+    }
+    return keys;
+  }
+
+  private find(publicKey: string): StoredKey {
+    const key = this.get(publicKey);
+    if (!key) {
+      throw new KeystoreError(`No key ${publicKey} is stored`);
+    }
+    return key;
+  }
+
+  private checkNewKey(publicKey: string, label: string): void {
+    this.checkLabel(label);
+    if (this.get(publicKey)) {
+      throw new KeystoreError(`Key ${publicKey} is already stored`);
+    }
+  }
+
+  private checkLabel(label: string, publicKey?: string): void {
+    if (!label.trim()) {
+      throw new KeystoreError('Keys must have a label');
+    }
+    const taken = this.data.keys.some(
+      key => key.label === label && key.publicKey !== publicKey
+    );
+    if (taken) {
+      throw new KeystoreError(`The label '${label}' is already in use`);
+    }
+  }
+
+  // Applies changes one at a time, each to the data saved by the one before,
+  // so that concurrent calls cannot overwrite or duplicate each other's keys.
+  private update(change: (data: KeystoreData) => KeystoreData): Promise<void> {
+    const updated = this.updates.then(async () => {
+      const data = change(this.data);
+      await this.backend.save(data);
+      this.data = data;
+    });
+    this.updates = updated.catch(() => undefined);
+    return updated;
+  }
+}
@@ -0,0 +1,97 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { installMockCanopy, MockCanopy } from './testing';
+import { publish, registerApp, SaplingContext } from './canopy';
+import { defineTopic } from './messaging';
+
+const refresh = defineTopic<void>('circuits', 'refresh');
+
+describe('sapling lifecycle', () => {
+  let canopy: MockCanopy;
+
+  beforeEach(() => {
+    canopy = installMockCanopy();
+  });
+
+  afterEach(() => {
+    canopy.uninstall();
+  });
+
+  it('should call the teardown function returned by bootstrap', () => {
+    const teardown = jest.fn();
+    registerApp(() => teardown);
+    canopy.mountApp(document.createElement('div'));
+    expect(teardown).not.toHaveBeenCalled();
+    canopy.unmountApp();
+    expect(teardown).toHaveBeenCalledTimes(1);
+    canopy.unmountApp();
+    expect(teardown).toHaveBeenCalledTimes(1);
+  });
+
+  it('should call the suspend, resume and unmount hooks', () => {
+    const calls: string[] = [];
+    registerApp(() => ({
+      suspend: () => calls.push('suspend'),
+      resume: () => calls.push('resume'),
+      unmount: () => calls.push('unmount')
+    }));
+    canopy.mountApp(document.createElement('div'));
+    canopy.resumeApp();
+    canopy.suspendApp();
+    canopy.suspendApp();
+    canopy.resumeApp();
+    canopy.unmountApp();
+    expect(calls).toEqual(['suspend', 'resume', 'unmount']);
+  });
+
+  it('should unmount the current app when another one is mounted', () => {
+    const teardown = jest.fn();
+    registerApp(() => teardown, { name: 'circuits' });
+    registerApp(() => undefined, { name: 'products' });
+    canopy.mountApp(document.createElement('div'), 'circuits');
+    canopy.mountApp(document.createElement('div'), 'products');
+    expect(teardown).toHaveBeenCalled();
+  });
+
+  it('should release subscriptions made through the context on unmount', () => {
+    const handler = jest.fn();
+    const routeListener = jest.fn();
+    let context = {} as SaplingContext;
+    registerApp(
+      (domNode, appContext) => {
+        context = appContext;
+        context.subscribe(refresh, handler);
+        context.router.onRouteChange(routeListener);
+      },
+      { name: 'circuits' }
+    );
+    canopy.mountApp(document.createElement('div'));
+    canopy.unmountApp();
+    canopy.mountApp(document.createElement('div'));
+    publish(refresh, undefined);
+    context.router.navigate('/proposals');
+    expect(handler).toHaveBeenCalledTimes(1);
+    expect(routeListener).toHaveBeenCalledTimes(1);
+  });
+
+  it('should unmount the app when the mock is uninstalled', () => {
+    const teardown = jest.fn();
+    registerApp(() => teardown);
+    canopy.mountApp(document.createElement('div'));
+    canopy.uninstall();
+    expect(teardown).toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,131 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  isCompatibleManifest,
+  loadManifest,
+  parseManifest,
+  SaplingManifest,
+  validateManifest
+} from './manifest';
+import { ManifestError } from './errors';
+import { Transport } from './transport';
+
+const manifest: SaplingManifest = {
+  name: 'circuits',
+  version: '0.3.1',
+  entry: 'circuits.js',
+  canopyApiVersion: '1.0.0',
+  configNamespaces: ['login'],
+  routes: ['/circuits'],
+  splinterEndpoints: ['/admin/circuits', '/admin/proposals'],
+  capabilities: ['keys:sign', 'user:read']
+};
+
+const yamlManifest = `
+name: circuits
+version: 0.3.1
+entry: circuits.js
+canopyApiVersion: 1.0.0
+configNamespaces:
+  - login
+routes:
+  - /circuits
+splinterEndpoints:
+  - /admin/circuits
+  - /admin/proposals
+capabilities:
+  - keys:sign
+  - user:read
+`;
+
+function fileTransport(body: string): Transport {
+  return async () => ({ status: 200, headers: {}, body });
+}
+
+describe('validateManifest(value)', () => {
+  it('should accept a complete manifest', () => {
+    expect(validateManifest(manifest)).toEqual([]);
+  });
+
+  it('should report every problem', () => {
+    expect(
+      validateManifest({
+        name: 'Circuits',
+        version: 'latest',
+        routes: ['circuits'],
+        splinterEndpoints: '/admin/circuits',
+        capabilities: ['keys:write']
+      })
+    ).toEqual([
+      "name 'Circuits' must be lower-case letters, digits and dashes",
+      "version 'latest' must be a semantic version",
+      'canopyApiVersion is required',
+      'entry is required',
+      "routes entry 'circuits' must be a path starting with /",
+      'splinterEndpoints must be a list',
+      "capabilities entry 'keys:write' must be one of keys:sign, keys:read, user:read, config:read"
+    ]);
+    expect(validateManifest(['circuits'])).toEqual([
+      'manifest must be an object'
+    ]);
+  });
+});
+
+describe('parseManifest(text)', () => {
+  it('should parse YAML and JSON manifests', () => {
+    expect(parseManifest(yamlManifest)).toEqual(manifest);
+    expect(parseManifest(JSON.stringify(manifest))).toEqual(manifest);
+  });
+
+  it('should reject malformed manifests', () => {
+    expect(() => parseManifest('name: [')).toThrow(ManifestError);
+    expect(() => parseManifest('name: circuits')).toThrow(
+      'version is required'
+    );
+  });
+});
+
+describe('isCompatibleManifest(manifest, canopyApiVersion)', () => {
+  it('should require the same major and at least the minimum version', () => {
+    expect(isCompatibleManifest(manifest, '1.0.0')).toBe(true);
+    expect(isCompatibleManifest(manifest, '1.2.0')).toBe(true);
+    expect(
+      isCompatibleManifest({ ...manifest, canopyApiVersion: '1.1.0' }, '1.0.9')
+    ).toBe(false);
+    expect(isCompatibleManifest(manifest, '2.0.0')).toBe(false);
+  });
+});
+
+describe('loadManifest(url, options)', () => {
+  it('should fetch and parse the manifest', async () => {
+    await expect(
+      loadManifest('/saplings/circuits.yaml', {
+        transport: fileTransport(yamlManifest)
+      })
+    ).resolves.toEqual(manifest);
+  });
+
+  it('should reject saplings that need a newer Canopy API', async () => {
+    await expect(
+      loadManifest('/saplings/circuits.yaml', {
+        transport: fileTransport(yamlManifest),
+        canopyApiVersion: '0.9.0'
+      })
+    ).rejects.toThrow(
+      "sapling 'circuits' requires Canopy API 1.0.0, but the host provides 0.9.0"
+    );
+  });
+});
@@ -0,0 +1,227 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import yaml from 'js-yaml';
+
+import { ManifestError } from './errors';
+import { Capability } from './canopy';
+import { http, RequestOptions } from './http';
+import { CAPABILITIES } from './permissions';
+
+/**
+ * Version of the Canopy API implemented by this release of SaplingJS.
+ */
+export const CANOPY_API_VERSION = '1.0.0';
+
+/**
+ * Describes a sapling, so that hosts and deployment scripts can check it
+ * before loading its bundle.
+ */
+export interface SaplingManifest {
+  /** Lower-case name, such as `circuits`, used to refer to the sapling. */
+  name: string;
+  /** Semantic version of the sapling. */
+  version: string;
+  /** Path or URL of the sapling's bundle. */
+  entry: string;
+  /** Minimum version of the Canopy API the sapling needs. */
+  canopyApiVersion: string;
+  displayName?: string;
+  description?: string;
+  /** Config namespaces the sapling registers config saplings for. */
+  configNamespaces?: string[];
+  /** Route prefixes the sapling's apps are mounted at. */
+  routes?: string[];
+  /** Splinter REST endpoints the sapling calls, such as `/admin/circuits`. */
+  splinterEndpoints?: string[];
+  /** Capabilities the sapling needs, such as `keys:sign`. */
+  capabilities?: Capability[];
+}
+
+const NAME_PATTERN = /^[a-z][a-z0-9-]*$/;
+const SEMVER_PATTERN = /^(\d+)\.(\d+)\.(\d+)(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?$/;
+
+function parseVersion(version: string): number[] | null {
+  const match = SEMVER_PATTERN.exec(version);
+  return match ? [match[1], match[2], match[3]].map(Number) : null;
+}
+
+function checkString(
+  errors: string[],
+  manifest: { [field: string]: unknown },
+  field: string,
+  required: boolean
+): void {
+  const value = manifest[field];
+  if (value === undefined) {
+    if (required) {
+      errors.push(`${field} is required`);
+    }
+  } else if (typeof value !== 'string' || value.length === 0) {
+    errors.push(`${field} must be a non-empty string`);
+  }
+}
+
+function checkStringList(
+  errors: string[],
+  manifest: { [field: string]: unknown },
+  field: string,
+  isValid: (item: string) => boolean,
+  description: string
+): void {
+  const value = manifest[field];
+  if (value === undefined) {
+    return;
+  }
+  if (!Array.isArray(value)) {
+    errors.push(`${field} must be a list`);
+    return;
+  }
+  value.forEach(item => {
+    if (typeof item !== 'string' || !isValid(item)) {
+      errors.push(`${field} entry '${item}' must be ${description}`);
+    }
+  });
+}
+
+/**
+ * Returns every problem with a parsed manifest, or an empty list if it is
+ * valid. Fields the manifest format does not define are ignored.
+ */
+export function validateManifest(value: unknown): string[] {
+  if (typeof value !== 'object' || value === null || Array.isArray(value)) {
+    return ['manifest must be an object'];
+  }
+  const manifest = value as { [field: string]: unknown };
+  const errors: string[] = [];
+
+  checkString(errors, manifest, 'name', true);
+  if (
+    typeof manifest.name === 'string' &&
+    manifest.name &&
+    !NAME_PATTERN.test(manifest.name)
+  ) {
+    errors.push(
+      `name '${manifest.name}' must be lower-case letters, digits and dashes`
+    );
+  }
+  ['version', 'canopyApiVersion'].forEach(field => {
+    checkString(errors, manifest, field, true);
+    const version = manifest[field];
+    if (typeof version === 'string' && version && !parseVersion(version)) {
+      errors.push(`${field} '${version}' must be a semantic version`);
+    }
+  });
+  checkString(errors, manifest, 'entry', true);
+  checkString(errors, manifest, 'displayName', false);
+  checkString(errors, manifest, 'description', false);
+
+  checkStringList(
+    errors,
+    manifest,
+    'configNamespaces',
+    namespace => namespace.length > 0,
+    'a non-empty string'
+  );
+  checkStringList(
+    errors,
+    manifest,
+    'routes',
+    route => route.charAt(0) === '/',
+    'a path starting with /'
+  );
+  checkStringList(
+    errors,
+    manifest,
+    'splinterEndpoints',
+    endpoint => endpoint.charAt(0) === '/',
+    'a path starting with /'
+  );
+  checkStringList(
+    errors,
+    manifest,
+    'capabilities',
+    capability => CAPABILITIES.includes(capability as Capability),
+    `one of ${CAPABILITIES.join(', ')}`
+  );
+  return errors;
+}
+
+/**
+ * Returns true if a host implementing the given Canopy API version can load
+ * the sapling: the major versions must match, and the host's version must be
+ * at least the one the sapling needs.
+ */
+export function isCompatibleManifest(
+  manifest: SaplingManifest,
+  canopyApiVersion = CANOPY_API_VERSION
+): boolean {
+  const required = parseVersion(manifest.canopyApiVersion);
+  const provided = parseVersion(canopyApiVersion);
+  if (!required || !provided || required[0] !== provided[0]) {
+    return false;
+  }
+  const difference = [1, 2]
+    .map(index => provided[index] - required[index])
+    .filter(delta => delta !== 0)[0];
+  return difference === undefined || difference > 0;
+}
+
+/**
+ * Parses and validates a manifest from the contents of a YAML or JSON file.
+ * @throws {ManifestError} if the manifest is malformed
+ */
+export function parseManifest(text: string): SaplingManifest {
+  let value: unknown;
+  try {
+    value = yaml.safeLoad(text);
+  } catch (err) {
+    throw new ManifestError([
+      `manifest is not valid YAML or JSON: ${err.message}`
+    ]);
+  }
+  const errors = validateManifest(value);
+  if (errors.length > 0) {
+    throw new ManifestError(errors);
+  }
+  return value as SaplingManifest;
+}
+
+export interface LoadManifestOptions extends RequestOptions {
+  /** Version of the Canopy API the host implements. */
+  canopyApiVersion?: string;
+}
+
+/**
+ * Fetches a sapling's manifest and checks that the host can load the sapling.
+ * @throws {ManifestError} if the manifest is malformed or the sapling needs
+ * an incompatible Canopy API version
+ */
+export async function loadManifest(
+  url: string,
+  {
+    canopyApiVersion = CANOPY_API_VERSION,
+    ...options
+  }: LoadManifestOptions = {}
+): Promise<SaplingManifest> {
+  const response = await http('GET', url, null, options);
+  const manifest = parseManifest(response.body);
+  if (!isCompatibleManifest(manifest, canopyApiVersion)) {
+    throw new ManifestError([
+      `sapling '${manifest.name}' requires Canopy API ${manifest.canopyApiVersion}, but the host provides ${canopyApiVersion}`
+    ]);
+  }
+  return manifest;
+}
@@ -0,0 +1,125 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { installMockCanopy, MockCanopy } from './testing';
+import {
+  publish,
+  registerApp,
+  reply,
+  request,
+  SaplingContext,
+  subscribe
+} from './canopy';
+import { defineTopic } from './messaging';
+import { SaplingMessageError } from './errors';
+
+const circuitSelected = defineTopic<{ circuitId: string }>(
+  'circuits',
+  'selected'
+);
+const circuitCount = defineTopic<{ status: string }, number>(
+  'circuits',
+  'count'
+);
+
+describe('sapling messaging', () => {
+  let canopy: MockCanopy;
+  let context: SaplingContext;
+
+  beforeEach(() => {
+    canopy = installMockCanopy();
+    registerApp(
+      (domNode, appContext) => {
+        context = appContext;
+      },
+      { name: 'circuits' }
+    );
+  });
+
+  afterEach(() => {
+    canopy.uninstall();
+  });
+
+  it('should namespace topic names', () => {
+    expect(circuitSelected.name).toEqual('circuits/selected');
+  });
+
+  it('should deliver messages to mounted saplings', () => {
+    const handler = jest.fn();
+    canopy.mountApp(document.createElement('div'));
+    context.subscribe(circuitSelected, handler);
+    publish(circuitSelected, { circuitId: 'abcde-01234' });
+    expect(handler).toHaveBeenCalledWith({ circuitId: 'abcde-01234' });
+  });
+
+  it('should not deliver messages to unmounted saplings', () => {
+    const handler = jest.fn();
+    canopy.mountApp(document.createElement('div'));
+    context.subscribe(circuitSelected, handler);
+    canopy.unmountApp();
+    publish(circuitSelected, { circuitId: 'abcde-01234' });
+    expect(handler).not.toHaveBeenCalled();
+  });
+
+  it('should release handlers registered through the exports on unmount', async () => {
+    const handler = jest.fn();
+    const replier = jest.fn(() => 1);
+    canopy.mountApp(document.createElement('div'));
+    subscribe(circuitSelected, handler);
+    reply(circuitCount, replier);
+    canopy.unmountApp();
+    publish(circuitSelected, { circuitId: 'abcde-01234' });
+    await expect(
+      request(circuitCount, { status: 'active' })
+    ).rejects.toBeInstanceOf(SaplingMessageError);
+    expect(handler).not.toHaveBeenCalled();
+    expect(replier).not.toHaveBeenCalled();
+  });
+
+  it('should stop delivering messages after unsubscribing', () => {
+    const handler = jest.fn();
+    canopy.mountApp(document.createElement('div'));
+    context.subscribe(circuitSelected, handler)();
+    publish(circuitSelected, { circuitId: 'abcde-01234' });
+    expect(handler).not.toHaveBeenCalled();
+  });
+
+  it('should resolve requests with the reply of a mounted sapling', async () => {
+    canopy.mountApp(document.createElement('div'));
+    context.reply(circuitCount, async ({ status }) =>
+      status === 'active' ? 3 : 0
+    );
+    await expect(request(circuitCount, { status: 'active' })).resolves.toEqual(
+      3
+    );
+  });
+
+  it('should reject requests no mounted sapling replies to', async () => {
+    canopy.mountApp(document.createElement('div'));
+    context.reply(circuitCount, () => 3);
+    canopy.unmountApp();
+    await expect(
+      request(circuitCount, { status: 'active' })
+    ).rejects.toBeInstanceOf(SaplingMessageError);
+  });
+
+  it('should reject requests that are not answered in time', async () => {
+    canopy.mountApp(document.createElement('div'));
+    context.reply(circuitCount, () => new Promise<number>(() => undefined));
+    await expect(
+      request(circuitCount, { status: 'active' }, { timeout: 1 })
+    ).rejects.toThrow('No reply to circuits/count within 1ms');
+  });
+});
@@ -0,0 +1,188 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { SaplingMessageError } from './errors';
+import { callListener, Unsubscribe } from './events';
+
+/**
+ * A typed topic saplings exchange messages on. `Payload` is the type of the
+ * messages published to the topic and `Reply` the type of the replies to
+ * requests sent to it.
+ *
+ * The `payload` and `reply` fields are never set; they only carry the types.
+ */
+export interface Topic<Payload, Reply = void> {
+  readonly name: string;
+  readonly payload?: Payload;
+  readonly reply?: Reply;
+}
+
+/**
+ * Declares a topic, namespaced by the sapling that owns it. Share the returned
+ * declaration between the saplings that publish and subscribe to it.
+ *
+ * @example
+ * const circuitSelected = defineTopic<{ circuitId: string }>(
+ *   'circuits',
+ *   'selected'
+ * );
+ */
+export function defineTopic<Payload, Reply = void>(
+  namespace: string,
+  name: string
+): Topic<Payload, Reply> {
+  return { name: `${namespace}/${name}` };
+}
+
+export interface MessageRequestOptions {
+  /** Time to wait for the reply, in milliseconds. Defaults to 5 seconds. */
+  timeout?: number;
+}
+
+export interface Publish {
+  <P>(topic: Topic<P, unknown>, payload: P): void;
+}
+
+export interface Subscribe {
+  <P>(topic: Topic<P, unknown>, handler: (payload: P) => void): Unsubscribe;
+}
+
+export interface Request {
+  <P, R>(
+    topic: Topic<P, R>,
+    payload: P,
+    options?: MessageRequestOptions
+  ): Promise<R>;
+}
+
+export interface Reply {
+  <P, R>(
+    topic: Topic<P, R>,
+    handler: (payload: P) => R | Promise<R>
+  ): Unsubscribe;
+}
+
+/**
+ * Publish/subscribe and request/reply between saplings.
+ */
+export interface SaplingMessaging {
+  publish: Publish;
+  subscribe: Subscribe;
+  request: Request;
+  reply: Reply;
+}
+
+export const DEFAULT_REQUEST_TIMEOUT = 5000;
+
+interface Handler {
+  isActive: () => boolean;
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  handle: (payload: any) => any;
+}
+
+interface HandlerTable {
+  [topic: string]: Handler[];
+}
+
+function addHandler(
+  table: HandlerTable,
+  topic: string,
+  handler: Handler
+): Unsubscribe {
+  // eslint-disable-next-line no-param-reassign
+  table[topic] = (table[topic] || []).concat(handler);
+  return () => {
+    // eslint-disable-next-line no-param-reassign
+    table[topic] = (table[topic] || []).filter(other => other !== handler);
+  };
+}
+
+function activeHandlers(table: HandlerTable, topic: string): Handler[] {
+  return (table[topic] || []).filter(handler => handler.isActive());
+}
+
+/**
+ * Routes messages between the saplings of a Canopy. Each sapling gets its own
+ * scope, whose handlers only receive messages while the sapling is mounted.
+ */
+export class MessageBus {
+  private subscribers: HandlerTable = {};
+
+  private repliers: HandlerTable = {};
+
+  /**
+   * Returns the messaging functions of a sapling.
+   * @param isActive - Returns whether the sapling is mounted. Handlers of the
+   * sapling are skipped while it returns `false`.
+   */
+  scope(isActive: () => boolean = () => true): SaplingMessaging {
+    return {
+      publish: (topic, payload) => {
+        activeHandlers(this.subscribers, topic.name).forEach(handler =>
+          callListener(handler.handle, payload)
+        );
+      },
+      subscribe: (topic, handle) =>
+        addHandler(this.subscribers, topic.name, { isActive, handle }),
+      request: (topic, payload, options) =>
+        this.request(topic, payload, options),
+      reply: (topic, handle) =>
+        addHandler(this.repliers, topic.name, { isActive, handle })
+    };
+  }
+
+  /**
+   * Sends a request to the first mounted sapling that replies to the topic.
+   */
+  private request<P, R>(
+    topic: Topic<P, R>,
+    payload: P,
+    { timeout = DEFAULT_REQUEST_TIMEOUT }: MessageRequestOptions = {}
+  ): Promise<R> {
+    const [replier] = activeHandlers(this.repliers, topic.name);
+    if (!replier) {
+      return Promise.reject(
+        new SaplingMessageError(
+          `No mounted sapling replies to ${topic.name}`,
+          topic.name
+        )
+      );
+    }
+    return new Promise<R>((resolve, reject) => {
+      const timer = setTimeout(
+        () =>
+          reject(
+            new SaplingMessageError(
+              `No reply to ${topic.name} within ${timeout}ms`,
+              topic.name
+            )
+          ),
+        timeout
+      );
+      Promise.resolve()
+        .then(() => replier.handle(payload))
+        .then(
+          reply => {
+            clearTimeout(timer);
+            resolve(reply);
+          },
+          err => {
+            clearTimeout(timer);
+            reject(err);
+          }
+        );
+    });
+  }
+}
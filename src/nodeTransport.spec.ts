@@ -0,0 +1,86 @@
+/**
+ * @jest-environment node
+ */
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import * as http from 'http';
+import { AddressInfo } from 'net';
+import { SplinterAbortError, SplinterTimeoutError } from './errors';
+import { nodeTransport } from './nodeTransport';
+
+// A signal that can be aborted from the test, as Node.js 12 has no
+// AbortController.
+function abortableSignal(): { signal: AbortSignal; abort: () => void } {
+  const listeners: (() => void)[] = [];
+  const signal = {
+    aborted: false,
+    addEventListener: (type: string, listener: () => void) =>
+      listeners.push(listener),
+    removeEventListener: jest.fn()
+  };
+  return {
+    signal: (signal as unknown) as AbortSignal,
+    abort: () => {
+      signal.aborted = true;
+      listeners.forEach(listener => listener());
+    }
+  };
+}
+
+describe('nodeTransport', () => {
+  let server: http.Server;
+  let url: string;
+  let onRequest: () => void;
+
+  beforeEach(done => {
+    onRequest = jest.fn();
+    // Starts the response but never ends it.
+    server = http.createServer((request, response) => {
+      response.writeHead(200, { 'Content-Type': 'application/json' });
+      response.write('{"data":');
+      onRequest();
+    });
+    server.listen(0, '127.0.0.1', () => {
+      const { port } = server.address() as AddressInfo;
+      url = `http://127.0.0.1:${port}/batches`;
+      done();
+    });
+  });
+
+  afterEach(done => {
+    server.close(() => done());
+  });
+
+  it('should reject when aborted after the response has started', async () => {
+    const { signal, abort } = abortableSignal();
+    onRequest = () => setTimeout(abort, 10);
+    await expect(
+      nodeTransport({ method: 'GET', url, headers: {}, body: null, signal })
+    ).rejects.toBeInstanceOf(SplinterAbortError);
+  });
+
+  it('should time out a response that never ends', async () => {
+    await expect(
+      nodeTransport({
+        method: 'GET',
+        url,
+        headers: {},
+        body: null,
+        timeout: 50
+      })
+    ).rejects.toBeInstanceOf(SplinterTimeoutError);
+  });
+});
@@ -0,0 +1,109 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import * as http from 'http';
+import * as https from 'https';
+import { URL } from 'url';
+import {
+  abortError,
+  HttpHeaders,
+  networkError,
+  timeoutError,
+  Transport
+} from './transport';
+
+/**
+ * Transport backed by the Node.js `http` and `https` modules, for using the
+ * SaplingJS clients from scripts and tests outside of a browser. It is not
+ * exported from the main entry point so that browser bundles do not pull in
+ * the Node.js modules; import it from `splinter-saplingjs/dist/nodeTransport`.
+ */
+export const nodeTransport: Transport = transportRequest =>
+  new Promise((resolve, reject) => {
+    const { method, url, headers, body, timeout, signal } = transportRequest;
+    if (signal && signal.aborted) {
+      reject(abortError(transportRequest));
+      return;
+    }
+
+    let target: URL;
+    try {
+      target = new URL(url);
+    } catch (err) {
+      reject(networkError(transportRequest, err.message));
+      return;
+    }
+
+    let failure: Error | null = null;
+    let started: http.IncomingMessage | null = null;
+    const client = target.protocol === 'https:' ? https : http;
+    const request = client.request(target, { method, headers });
+    const settle = (): void => {
+      if (signal) {
+        // eslint-disable-next-line no-use-before-define, @typescript-eslint/no-use-before-define
+        signal.removeEventListener('abort', onAbort);
+      }
+    };
+    const fail = (err: Error): void => {
+      settle();
+      reject(failure || networkError(transportRequest, err.message));
+    };
+    const cancel = (err: Error): void => {
+      failure = err;
+      request.abort();
+      if (started) {
+        // Aborting the request does not end a response that has already
+        // started, which would otherwise never settle.
+        started.destroy();
+        fail(err);
+      }
+    };
+    const onAbort = (): void => cancel(abortError(transportRequest));
+
+    request.on('response', (response: http.IncomingMessage) => {
+      started = response;
+      const chunks: Buffer[] = [];
+      response.on('data', (chunk: Buffer) => chunks.push(chunk));
+      response.on('error', fail);
+      response.on('end', () => {
+        settle();
+        const responseHeaders: HttpHeaders = {};
+        Object.keys(response.headers).forEach(name => {
+          const value = response.headers[name];
+          responseHeaders[name] = Array.isArray(value)
+            ? value.join(', ')
+            : value || '';
+        });
+        resolve({
+          status: response.statusCode || 0,
+          headers: responseHeaders,
+          body: Buffer.concat(chunks).toString('utf8')
+        });
+      });
+    });
+    request.on('error', fail);
+    if (timeout) {
+      request.setTimeout(timeout, () =>
+        cancel(timeoutError(transportRequest))
+      );
+    }
+    if (signal) {
+      signal.addEventListener('abort', onAbort);
+    }
+    if (body !== null) {
+      request.write(body);
+    }
+    request.end();
+  });
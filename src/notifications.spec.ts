@@ -0,0 +1,77 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { installMockCanopy, MockCanopy } from './testing';
+import {
+  dismissNotification,
+  getNotifications,
+  markNotificationRead,
+  notify,
+  onNotificationsChange
+} from './canopy';
+
+describe('notifications', () => {
+  let canopy: MockCanopy;
+
+  beforeEach(() => {
+    canopy = installMockCanopy();
+  });
+
+  afterEach(() => {
+    canopy.uninstall();
+  });
+
+  it('should list raised notifications newest first', () => {
+    notify({ title: 'First' });
+    const id = notify({
+      level: 'warning',
+      title: 'Second',
+      body: 'Details',
+      persistent: true
+    });
+    expect(getNotifications()).toMatchObject([
+      {
+        id,
+        level: 'warning',
+        title: 'Second',
+        body: 'Details',
+        actions: [],
+        persistent: true,
+        read: false
+      },
+      { level: 'info', title: 'First', persistent: false, read: false }
+    ]);
+  });
+
+  it('should track read state and dismissal', () => {
+    const id = notify({ title: 'Proposal accepted' });
+    markNotificationRead(id);
+    expect(getNotifications()[0].read).toBe(true);
+    dismissNotification(id);
+    expect(getNotifications()).toEqual([]);
+  });
+
+  it('should notify subscribers of changes until they unsubscribe', () => {
+    const listener = jest.fn();
+    const unsubscribe = onNotificationsChange(listener);
+    const id = notify({ title: 'Proposal accepted' });
+    markNotificationRead(id);
+    markNotificationRead(id);
+    unsubscribe();
+    dismissNotification(id);
+    expect(listener).toHaveBeenCalledTimes(2);
+    expect(listener.mock.calls[1][0][0].read).toBe(true);
+  });
+});
@@ -0,0 +1,180 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { Emitter, Unsubscribe } from './events';
+
+export type NotificationLevel = 'info' | 'success' | 'warning' | 'error';
+
+export interface NotificationAction {
+  label: string;
+  /** Called when the user picks the action. */
+  onSelect?: () => void;
+  /** Navigates to this path when the user picks the action. */
+  href?: string;
+}
+
+export interface NotificationOptions {
+  /** Defaults to 'info'. */
+  level?: NotificationLevel;
+  title: string;
+  body?: string;
+  actions?: NotificationAction[];
+  /**
+   * Keeps the notification until it is dismissed, rather than letting the
+   * notifications sapling hide it after a short time.
+   */
+  persistent?: boolean;
+}
+
+/**
+ * A notification raised with `notify`.
+ */
+export interface SaplingNotification {
+  id: string;
+  level: NotificationLevel;
+  title: string;
+  body?: string;
+  actions: NotificationAction[];
+  persistent: boolean;
+  read: boolean;
+  /** When the notification was raised, in milliseconds since the epoch. */
+  createdAt: number;
+}
+
+export interface Notify {
+  /** Raises a notification and returns its ID. */
+  (options: NotificationOptions): string;
+}
+
+export interface GetNotifications {
+  /** Returns the notifications that have not been dismissed, newest first. */
+  (): SaplingNotification[];
+}
+
+export interface MarkNotificationRead {
+  (id: string): void;
+}
+
+export interface DismissNotification {
+  (id: string): void;
+}
+
+export interface OnNotificationsChange {
+  /**
+   * Calls the listener with every notification each time one is raised,
+   * read or dismissed.
+   */
+  (listener: (notifications: SaplingNotification[]) => void): Unsubscribe;
+}
+
+/**
+ * Notifications raised by saplings and rendered by the sapling registered
+ * for the 'notifications' config namespace.
+ */
+export interface SaplingNotifications {
+  notify: Notify;
+  getNotifications: GetNotifications;
+  markNotificationRead: MarkNotificationRead;
+  dismissNotification: DismissNotification;
+  onNotificationsChange: OnNotificationsChange;
+}
+
+/**
+ * Keeps the notifications of a host in memory.
+ */
+export class NotificationCenter {
+  private notifications: SaplingNotification[] = [];
+
+  private changes = new Emitter<SaplingNotification[]>();
+
+  private nextId = 1;
+
+  notify({
+    level = 'info',
+    title,
+    body,
+    actions = [],
+    persistent = false
+  }: NotificationOptions): string {
+    const id = `notification-${this.nextId}`;
+    this.nextId += 1;
+    this.notifications = [
+      {
+        id,
+        level,
+        title,
+        body,
+        actions,
+        persistent,
+        read: false,
+        createdAt: Date.now()
+      },
+      ...this.notifications
+    ];
+    this.emitChange();
+    return id;
+  }
+
+  getNotifications(): SaplingNotification[] {
+    return this.notifications.slice();
+  }
+
+  markRead(id: string): void {
+    let changed = false;
+    this.notifications = this.notifications.map(notification => {
+      if (notification.id !== id || notification.read) {
+        return notification;
+      }
+      changed = true;
+      return { ...notification, read: true };
+    });
+    if (changed) {
+      this.emitChange();
+    }
+  }
+
+  dismiss(id: string): void {
+    const remaining = this.notifications.filter(
+      notification => notification.id !== id
+    );
+    if (remaining.length !== this.notifications.length) {
+      this.notifications = remaining;
+      this.emitChange();
+    }
+  }
+
+  subscribe(
+    listener: (notifications: SaplingNotification[]) => void
+  ): Unsubscribe {
+    return this.changes.subscribe(listener);
+  }
+
+  /**
+   * Returns the notifications functions of the `Canopy` interface.
+   */
+  api(): SaplingNotifications {
+    return {
+      notify: options => this.notify(options),
+      getNotifications: () => this.getNotifications(),
+      markNotificationRead: id => this.markRead(id),
+      dismissNotification: id => this.dismiss(id),
+      onNotificationsChange: listener => this.subscribe(listener)
+    };
+  }
+
+  private emitChange(): void {
+    this.changes.emit(this.getNotifications());
+  }
+}
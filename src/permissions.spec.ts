@@ -0,0 +1,207 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { installMockCanopy, MockCanopy } from './testing';
+import {
+  Capability,
+  getKeys,
+  getUser,
+  registerApp,
+  SaplingContext,
+  setKeys,
+  sign
+} from './canopy';
+import { defineConfigNamespace, LOGIN_NAMESPACE } from './configNamespaces';
+import { publicKeyFromPrivate, verify } from './crypto';
+import { PermissionDeniedError } from './errors';
+import { CAPABILITIES, hasCapability } from './permissions';
+
+const message = new Uint8Array([1, 2, 3]);
+
+describe('sapling capabilities', () => {
+  const privateKey = 'a'.repeat(64);
+  const keys = { privateKey, publicKey: publicKeyFromPrivate(privateKey) };
+  let canopy: MockCanopy;
+
+  function mount(capabilities?: Capability[]): SaplingContext {
+    let context = {} as SaplingContext;
+    registerApp(
+      (domNode, appContext) => {
+        context = appContext;
+      },
+      { name: 'circuits', capabilities }
+    );
+    canopy.mountApp(document.createElement('div'));
+    return context;
+  }
+
+  beforeEach(() => {
+    canopy = installMockCanopy({
+      user: { userId: 'alice' },
+      keys,
+      manifests: [
+        { name: 'circuits', capabilities: CAPABILITIES },
+        { name: 'signer', capabilities: ['keys:sign'] }
+      ]
+    });
+  });
+
+  afterEach(() => {
+    canopy.uninstall();
+  });
+
+  it('should deny every guarded call by default', async () => {
+    const { canopy: guarded } = mount();
+    expect(() => guarded.getUser()).toThrow(PermissionDeniedError);
+    expect(() => guarded.getKeys()).toThrow(PermissionDeniedError);
+    expect(() => guarded.getSharedConfig()).toThrow(PermissionDeniedError);
+    await expect(guarded.sign(message)).rejects.toBeInstanceOf(
+      PermissionDeniedError
+    );
+  });
+
+  it('should report the sapling and the missing capability', () => {
+    expect.assertions(3);
+    const { canopy: guarded } = mount(['user:read']);
+    try {
+      guarded.getKeys();
+    } catch (err) {
+      expect(err.message).toEqual(
+        "Sapling 'circuits' lacks the 'keys:read' capability"
+      );
+      expect(err.saplingName).toEqual('circuits');
+      expect(err.capability).toEqual('keys:read');
+    }
+  });
+
+  it('should sign without revealing the private key', async () => {
+    const { canopy: guarded } = mount(['keys:sign']);
+    const signature = await guarded.sign(message);
+    expect(guarded.getPublicKey()).toEqual(keys.publicKey);
+    expect(verify(message, signature, keys.publicKey)).toBe(true);
+    expect(() => guarded.getKeys()).toThrow(PermissionDeniedError);
+  });
+
+  it('should not register apps with capabilities the sapling lacks', () => {
+    const bootstrap = jest.fn();
+    const { canopy: guarded } = mount(['apps:register']);
+    expect(() =>
+      guarded.registerApp(bootstrap, { capabilities: ['keys:read'] })
+    ).toThrow(PermissionDeniedError);
+    expect(canopy.apps.map(app => app.name)).toEqual(['circuits']);
+
+    canopy.unmountApp();
+    const { canopy: unregistered } = mount(['keys:read']);
+    expect(() => unregistered.registerApp(bootstrap)).toThrow(
+      PermissionDeniedError
+    );
+  });
+
+  it('should register apps with capabilities the sapling holds', () => {
+    const { canopy: guarded } = mount(['apps:register', 'keys:sign']);
+    guarded.registerApp(jest.fn(), {
+      name: 'signer',
+      capabilities: ['keys:sign']
+    });
+    expect(canopy.apps.map(app => app.name)).toEqual(['circuits', 'signer']);
+  });
+
+  it('should not register config saplings needing missing capabilities', () => {
+    const { canopy: guarded } = mount(['apps:register']);
+    expect(() =>
+      guarded.registerConfigSapling(LOGIN_NAMESPACE, async () => ({
+        user: { userId: 'mallory' },
+        keys
+      }))
+    ).toThrow(PermissionDeniedError);
+    expect(canopy.configSaplings).toHaveLength(0);
+  });
+
+  it('should limit config namespaces to their capabilities', async () => {
+    const namespace = defineConfigNamespace<void, void>('escalate', {
+      apply: (output, guarded) => guarded.setKeys(null)
+    });
+    canopy.registerConfigSapling(namespace, () => undefined);
+    await expect(canopy.runConfigSaplings()).rejects.toBeInstanceOf(
+      PermissionDeniedError
+    );
+    expect(canopy.getKeys()).toEqual(keys);
+  });
+
+  it('should not let saplings replace the user or keys', () => {
+    const { canopy: guarded } = mount(['keys:read', 'user:read']);
+    expect(() => guarded.setKeys(keys)).toThrow(PermissionDeniedError);
+    expect(() => guarded.setUser({ userId: 'mallory' })).toThrow(
+      PermissionDeniedError
+    );
+  });
+
+  it('should guard the exported functions of the mounted app', async () => {
+    mount(['user:read']);
+    expect(getUser()).toEqual({ userId: 'alice' });
+    expect(() => getKeys()).toThrow(PermissionDeniedError);
+    expect(() => setKeys(null)).toThrow(PermissionDeniedError);
+    await expect(sign(message)).rejects.toBeInstanceOf(PermissionDeniedError);
+
+    canopy.unmountApp();
+    expect(getKeys()).toEqual(keys);
+  });
+
+  it('should only grant capabilities approved by a manifest', () => {
+    expect(() =>
+      registerApp(jest.fn(), { name: 'rogue', capabilities: ['keys:read'] })
+    ).toThrow(PermissionDeniedError);
+    expect(() =>
+      registerApp(jest.fn(), { name: 'signer', capabilities: ['keys:read'] })
+    ).toThrow(PermissionDeniedError);
+    expect(canopy.apps).toHaveLength(0);
+  });
+
+  it('should copy the capabilities an app is registered with', () => {
+    const capabilities: Capability[] = ['user:read'];
+    const { canopy: guarded } = mount(capabilities);
+    capabilities.push('keys:read');
+    expect(() => guarded.getKeys()).toThrow(PermissionDeniedError);
+    expect(() => getKeys()).toThrow(PermissionDeniedError);
+  });
+
+  it('should hide the host records and lifecycle from window', () => {
+    mount(['user:read']);
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    const installed = (window as any).$CANOPY;
+    expect(installed.mountedApp).toBeUndefined();
+    expect(installed.apps).toBeUndefined();
+    expect(installed.configSaplings).toBeUndefined();
+    expect(installed.unmountApp).toBeUndefined();
+    expect(installed.mountApp).toBeUndefined();
+    expect(installed.runConfigSaplings).toBeUndefined();
+    expect(() => getKeys()).toThrow(PermissionDeniedError);
+  });
+
+  it('should allow granted capabilities', () => {
+    const { canopy: guarded } = mount(['user:read', 'config:read']);
+    expect(guarded.getUser()).toEqual({ userId: 'alice' });
+    expect(guarded.getSharedConfig().canopyConfig.splinterURL).toEqual(
+      'http://localhost:8080'
+    );
+  });
+});
+
+describe('hasCapability(granted, required)', () => {
+  it('should let keys:read imply keys:sign', () => {
+    expect(hasCapability(['keys:read'], 'keys:sign')).toBe(true);
+    expect(hasCapability(['keys:sign'], 'keys:read')).toBe(false);
+  });
+});
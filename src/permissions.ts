@@ -0,0 +1,164 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  AppOptions,
+  BuiltInConfigNamespace,
+  Canopy,
+  Capability,
+  ConfigNamespace,
+  RegisterConfigSapling
+} from './canopy';
+import { resolveConfigNamespace } from './configNamespaces';
+import { PermissionDeniedError } from './errors';
+
+/**
+ * Every capability a sapling can be granted.
+ */
+export const CAPABILITIES: Capability[] = [
+  'keys:sign',
+  'keys:read',
+  'keys:write',
+  'user:read',
+  'user:write',
+  'config:read',
+  'apps:register'
+];
+
+/**
+ * Returns true if the granted capabilities include the required one. Reading
+ * the keys implies signing with them.
+ */
+export function hasCapability(
+  granted: ReadonlyArray<Capability>,
+  required: Capability
+): boolean {
+  return (
+    granted.includes(required) ||
+    (required === 'keys:sign' && granted.includes('keys:read'))
+  );
+}
+
+/**
+ * A sapling calling Canopy, and the capabilities it was granted.
+ */
+export interface CanopyCaller {
+  saplingName: string;
+  capabilities: ReadonlyArray<Capability>;
+}
+
+/**
+ * Limits a Canopy to the capabilities of the sapling calling it, looked up
+ * with `caller` on every call. Calls are unrestricted while `caller` returns
+ * `null`, which hosts use for calls made by Canopy itself.
+ * @param canopy - The unrestricted Canopy.
+ * @param caller - Returns the sapling calling the Canopy, if any.
+ */
+export function guardCanopyForCaller(
+  canopy: Canopy,
+  caller: () => CanopyCaller | null
+): Canopy {
+  const check = (capability: Capability): void => {
+    const current = caller();
+    if (current && !hasCapability(current.capabilities, capability)) {
+      throw new PermissionDeniedError(current.saplingName, capability);
+    }
+  };
+
+  // Saplings can only pass on capabilities they hold, so that registering a
+  // sapling is not a way to gain more.
+  const checkRegistration = (capabilities: Capability[] = []): void => {
+    check('apps:register');
+    capabilities.forEach(check);
+  };
+
+  // Forwards calls to the Canopy, after checking the capability if one is
+  // required.
+  function forward<K extends keyof Canopy>(
+    name: K,
+    capability?: Capability
+  ): Canopy[K] {
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    const forwarded = (...args: any[]): any => {
+      if (capability) {
+        check(capability);
+      }
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      const fn = canopy[name] as (...params: any[]) => any;
+      return fn(...args);
+    };
+    return forwarded as Canopy[K];
+  }
+
+  const registerConfigSapling = (
+    configNamespace: ConfigNamespace<unknown, unknown> | BuiltInConfigNamespace,
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    bootstrapFunction: any
+  ): void => {
+    checkRegistration(resolveConfigNamespace(configNamespace).capabilities);
+    canopy.registerConfigSapling(
+      configNamespace as ConfigNamespace<unknown, unknown>,
+      bootstrapFunction
+    );
+  };
+
+  return {
+    registerApp: (bootstrapFunction, options: AppOptions = {}) => {
+      checkRegistration(options.capabilities);
+      canopy.registerApp(bootstrapFunction, options);
+    },
+    registerConfigSapling: registerConfigSapling as RegisterConfigSapling,
+    getUser: forward('getUser', 'user:read'),
+    setUser: forward('setUser', 'user:write'),
+    onUserChange: forward('onUserChange', 'user:read'),
+    getKeys: forward('getKeys', 'keys:read'),
+    setKeys: forward('setKeys', 'keys:write'),
+    onKeysChange: forward('onKeysChange', 'keys:read'),
+    sign: async message => {
+      check('keys:sign');
+      return canopy.sign(message);
+    },
+    getPublicKey: forward('getPublicKey', 'keys:sign'),
+    setKeySigner: forward('setKeySigner', 'keys:write'),
+    getSharedConfig: forward('getSharedConfig', 'config:read'),
+    hideCanopy: forward('hideCanopy'),
+    publish: forward('publish'),
+    subscribe: forward('subscribe'),
+    request: forward('request'),
+    reply: forward('reply'),
+    notify: forward('notify'),
+    getNotifications: forward('getNotifications'),
+    markNotificationRead: forward('markNotificationRead'),
+    dismissNotification: forward('dismissNotification'),
+    onNotificationsChange: forward('onNotificationsChange')
+  };
+}
+
+/**
+ * Limits a Canopy to the capabilities granted to a sapling. Hosts hand the
+ * returned view to the sapling in place of the Canopy itself, so that a
+ * sapling granted only `keys:sign` can sign payloads without ever reading the
+ * private key.
+ * @param canopy - The unrestricted Canopy.
+ * @param saplingName - Name reported in `PermissionDeniedError`s.
+ * @param capabilities - Capabilities granted to the sapling.
+ */
+export function guardCanopy(
+  canopy: Canopy,
+  saplingName: string,
+  capabilities: ReadonlyArray<Capability>
+): Canopy {
+  return guardCanopyForCaller(canopy, () => ({ saplingName, capabilities }));
+}
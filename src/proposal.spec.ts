@@ -0,0 +1,117 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { encodeCircuitCreateRequest } from './admin';
+import { CircuitProposalError } from './errors';
+import { CircuitProposalBuilder } from './proposal';
+
+function validBuilder(): CircuitProposalBuilder {
+  return new CircuitProposalBuilder()
+    .withCircuitId('AAAAA-BBBBB')
+    .withManagementType('gameroom')
+    .withMember('alpha', ['tcps://splinterd-alpha:8044'])
+    .withMember('beta', ['tcps://splinterd-beta:8044'])
+    .withService('a000', 'scabbard', ['alpha'], { version: '2' })
+    .withService('b000', 'scabbard', ['beta'], { version: '2' })
+    .withApplicationMetadata(new Uint8Array([1, 2, 255]))
+    .withComments('a game');
+}
+
+describe('CircuitProposalBuilder', () => {
+  it('should build a valid proposal', () => {
+    const circuit = validBuilder().build();
+    expect(circuit.circuitId).toEqual('AAAAA-BBBBB');
+    expect(circuit.members.map(member => member.nodeId)).toEqual([
+      'alpha',
+      'beta'
+    ]);
+    expect(circuit.applicationMetadata).toEqual('0102ff');
+    expect(circuit.authorizationType).toEqual('Trust');
+  });
+
+  it('should serialize the proposal as a circuit create request', () => {
+    const builder = validBuilder();
+    expect(builder.toBytes()).toEqual(
+      encodeCircuitCreateRequest(builder.build())
+    );
+  });
+
+  it('should report malformed IDs', () => {
+    const errors = new CircuitProposalBuilder()
+      .withCircuitId('not-a-circuit')
+      .withManagementType('gameroom')
+      .withMember('alpha node', ['tcps://alpha:8044'])
+      .withService('toolong', 'scabbard', ['alpha node'])
+      .validate();
+    expect(errors).toEqual([
+      expect.stringContaining("circuit ID 'not-a-circuit'"),
+      "node ID 'alpha node' is not valid",
+      "service ID 'toolong' must be four alphanumeric characters"
+    ]);
+  });
+
+  it('should report services allocated to unknown members', () => {
+    expect(() =>
+      validBuilder()
+        .withService('c000', 'scabbard', ['gamma'])
+        .build()
+    ).toThrow(CircuitProposalError);
+    expect(
+      validBuilder()
+        .withService('c000', 'scabbard', ['gamma'])
+        .validate()
+    ).toEqual([
+      "service 'c000' is allocated to 'gamma', which is not a member"
+    ]);
+  });
+
+  it('should report duplicate service IDs and members', () => {
+    expect(
+      validBuilder()
+        .withService('a000', 'scabbard', ['beta'])
+        .withMember('beta', ['tcps://splinterd-beta:8044'])
+        .validate()
+    ).toEqual([
+      "member 'beta' is listed more than once",
+      "service ID 'a000' is used more than once"
+    ]);
+  });
+
+  it('should report members without endpoints', () => {
+    expect(
+      validBuilder()
+        .withMember('gamma', [])
+        .validate()
+    ).toEqual(["member 'gamma' has no endpoints"]);
+  });
+
+  it('should require public keys for challenge authorization', () => {
+    expect(
+      validBuilder()
+        .withAuthorizationType('Challenge')
+        .validate()
+    ).toEqual([
+      "member 'alpha' requires a public key for challenge authorization",
+      "member 'beta' requires a public key for challenge authorization"
+    ]);
+  });
+
+  it('should reject an invalid proposal instead of throwing', async () => {
+    const proposal = validBuilder()
+      .withMember('gamma', [])
+      .propose({ requesterNodeId: 'alpha' });
+    await expect(proposal).rejects.toBeInstanceOf(CircuitProposalError);
+  });
+});
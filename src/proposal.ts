@@ -0,0 +1,247 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  AuthorizationType,
+  CircuitMember,
+  CircuitService,
+  encodeCircuitCreateRequest,
+  proposeCircuit,
+  ProposedCircuit,
+  SubmitOptions
+} from './admin';
+import { bytesToHex } from './encoding';
+import { CircuitProposalError } from './errors';
+
+const CIRCUIT_ID_PATTERN = /^[a-zA-Z0-9]{5}-[a-zA-Z0-9]{5}$/;
+const NODE_ID_PATTERN = /^[a-zA-Z0-9_-]+$/;
+const SERVICE_ID_PATTERN = /^[a-zA-Z0-9]{4}$/;
+const ENDPOINT_PATTERN = /^[a-z0-9]+:\/\/.+/;
+const HEX_PATTERN = /^([0-9a-fA-F]{2})*$/;
+
+function duplicates(values: string[]): string[] {
+  return values.filter(
+    (value, index) =>
+      values.indexOf(value) !== index && values.indexOf(value, index + 1) === -1
+  );
+}
+
+/**
+ * Builds and validates a circuit proposal, so that mistakes are reported
+ * before the proposal is submitted to the admin service.
+ */
+export class CircuitProposalBuilder {
+  private circuitId = '';
+
+  private members: CircuitMember[] = [];
+
+  private roster: CircuitService[] = [];
+
+  private managementType = '';
+
+  private authorizationType: AuthorizationType = 'Trust';
+
+  private applicationMetadata = '';
+
+  private comments?: string;
+
+  private displayName?: string;
+
+  withCircuitId(circuitId: string): CircuitProposalBuilder {
+    this.circuitId = circuitId;
+    return this;
+  }
+
+  /**
+   * Adds a member node to the circuit.
+   * @param {string}    nodeId    The ID of the node
+   * @param {string[]}  endpoints The endpoints the node can be reached on
+   * @param {string}    publicKey The node's hex public key, required for
+   *                              challenge authorization
+   */
+  withMember(
+    nodeId: string,
+    endpoints: string[],
+    publicKey?: string
+  ): CircuitProposalBuilder {
+    this.members.push({ nodeId, endpoints, publicKey });
+    return this;
+  }
+
+  /**
+   * Adds a service to the circuit roster.
+   * @param {string}    serviceId     The four character ID of the service
+   * @param {string}    serviceType   The type of the service, e.g. 'scabbard'
+   * @param {string[]}  allowedNodes  The members the service may run on
+   * @param {object}    args          Arguments passed to the service
+   */
+  withService(
+    serviceId: string,
+    serviceType: string,
+    allowedNodes: string[],
+    args: { [key: string]: string } = {}
+  ): CircuitProposalBuilder {
+    this.roster.push({ serviceId, serviceType, allowedNodes, arguments: args });
+    return this;
+  }
+
+  withManagementType(managementType: string): CircuitProposalBuilder {
+    this.managementType = managementType;
+    return this;
+  }
+
+  withAuthorizationType(
+    authorizationType: AuthorizationType
+  ): CircuitProposalBuilder {
+    this.authorizationType = authorizationType;
+    return this;
+  }
+
+  /**
+   * Sets the application metadata, given as bytes or a hex string.
+   */
+  withApplicationMetadata(
+    metadata: Uint8Array | string
+  ): CircuitProposalBuilder {
+    this.applicationMetadata =
+      typeof metadata === 'string' ? metadata : bytesToHex(metadata);
+    return this;
+  }
+
+  withComments(comments: string): CircuitProposalBuilder {
+    this.comments = comments;
+    return this;
+  }
+
+  withDisplayName(displayName: string): CircuitProposalBuilder {
+    this.displayName = displayName;
+    return this;
+  }
+
+  /**
+   * Returns every problem with the proposal, or an empty list if it is valid.
+   */
+  validate(): string[] {
+    const errors: string[] = [];
+    const nodeIds = this.members.map(member => member.nodeId);
+    const serviceIds = this.roster.map(service => service.serviceId);
+
+    if (!CIRCUIT_ID_PATTERN.test(this.circuitId)) {
+      errors.push(
+        `circuit ID '${this.circuitId}' must be two groups of five alphanumeric characters separated by a dash`
+      );
+    }
+    if (!this.managementType) {
+      errors.push('management type is required');
+    }
+    if (!HEX_PATTERN.test(this.applicationMetadata)) {
+      errors.push('application metadata must be hex encoded');
+    }
+
+    if (this.members.length === 0) {
+      errors.push('circuit must have at least one member');
+    }
+    this.members.forEach(({ nodeId, endpoints, publicKey }) => {
+      if (!NODE_ID_PATTERN.test(nodeId)) {
+        errors.push(`node ID '${nodeId}' is not valid`);
+      }
+      if (endpoints.length === 0) {
+        errors.push(`member '${nodeId}' has no endpoints`);
+      }
+      endpoints
+        .filter(endpoint => !ENDPOINT_PATTERN.test(endpoint))
+        .forEach(endpoint => {
+          errors.push(`endpoint '${endpoint}' of '${nodeId}' is not valid`);
+        });
+      if (this.authorizationType === 'Challenge' && !publicKey) {
+        errors.push(
+          `member '${nodeId}' requires a public key for challenge authorization`
+        );
+      }
+    });
+    duplicates(nodeIds).forEach(nodeId => {
+      errors.push(`member '${nodeId}' is listed more than once`);
+    });
+
+    if (this.roster.length === 0) {
+      errors.push('circuit must have at least one service');
+    }
+    this.roster.forEach(({ serviceId, serviceType, allowedNodes }) => {
+      if (!SERVICE_ID_PATTERN.test(serviceId)) {
+        errors.push(
+          `service ID '${serviceId}' must be four alphanumeric characters`
+        );
+      }
+      if (!serviceType) {
+        errors.push(`service '${serviceId}' has no service type`);
+      }
+      if (allowedNodes.length === 0) {
+        errors.push(`service '${serviceId}' is not allocated to any member`);
+      }
+      allowedNodes
+        .filter(nodeId => !nodeIds.includes(nodeId))
+        .forEach(nodeId => {
+          errors.push(
+            `service '${serviceId}' is allocated to '${nodeId}', which is not a member`
+          );
+        });
+    });
+    duplicates(serviceIds).forEach(serviceId => {
+      errors.push(`service ID '${serviceId}' is used more than once`);
+    });
+
+    return errors;
+  }
+
+  /**
+   * Validates and returns the proposed circuit.
+   * @throws {CircuitProposalError} if the proposal is not valid
+   */
+  build(): ProposedCircuit {
+    const errors = this.validate();
+    if (errors.length > 0) {
+      throw new CircuitProposalError(errors);
+    }
+    return {
+      circuitId: this.circuitId,
+      roster: this.roster.map(service => ({ ...service })),
+      members: this.members.map(member => ({ ...member })),
+      authorizationType: this.authorizationType,
+      persistence: 'Any',
+      durability: 'NoDurability',
+      routes: 'Any',
+      managementType: this.managementType,
+      applicationMetadata: this.applicationMetadata,
+      comments: this.comments,
+      displayName: this.displayName
+    };
+  }
+
+  /**
+   * Validates the proposal and serializes it as the `CircuitCreateRequest`
+   * submitted to the admin service.
+   */
+  toBytes(): Uint8Array {
+    return encodeCircuitCreateRequest(this.build());
+  }
+
+  /**
+   * Validates the proposal and submits it to the admin service.
+   * @param {object}  options   Requester, signer and request options
+   */
+  async propose(options: SubmitOptions): Promise<void> {
+    await proposeCircuit(this.build(), options);
+  }
+}
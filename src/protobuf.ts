@@ -0,0 +1,96 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { utf8Encode } from './encoding';
+
+const VARINT = 0;
+const LENGTH_DELIMITED = 2;
+
+function varint(value: number): number[] {
+  const bytes: number[] = [];
+  let remaining = value;
+  while (remaining > 0x7f) {
+    bytes.push((remaining % 0x80) + 0x80);
+    remaining = Math.floor(remaining / 0x80);
+  }
+  bytes.push(remaining);
+  return bytes;
+}
+
+/**
+ * Minimal protocol buffer writer for the handful of Splinter and Sawtooth
+ * messages built by SaplingJS. Fields must be written in field number order,
+ * and scalar fields holding their default value are omitted, matching the
+ * canonical proto3 encoding.
+ */
+export class ProtobufWriter {
+  private buffer: number[] = [];
+
+  private tag(field: number, wireType: number): void {
+    this.buffer.push(...varint(field * 8 + wireType));
+  }
+
+  private delimited(field: number, value: Uint8Array): void {
+    this.tag(field, LENGTH_DELIMITED);
+    this.buffer.push(...varint(value.length));
+    for (let i = 0; i < value.length; i += 1) {
+      this.buffer.push(value[i]);
+    }
+  }
+
+  /** Writes an unsigned integer, enum or bool field. */
+  uint(field: number, value: number | boolean): ProtobufWriter {
+    const number = Number(value);
+    if (number !== 0) {
+      this.tag(field, VARINT);
+      this.buffer.push(...varint(number));
+    }
+    return this;
+  }
+
+  string(field: number, value: string | undefined): ProtobufWriter {
+    if (value) {
+      this.delimited(field, utf8Encode(value));
+    }
+    return this;
+  }
+
+  bytes(field: number, value: Uint8Array | undefined): ProtobufWriter {
+    if (value && value.length > 0) {
+      this.delimited(field, value);
+    }
+    return this;
+  }
+
+  /** Writes an embedded message, given its serialized bytes. */
+  message(field: number, value: Uint8Array): ProtobufWriter {
+    this.delimited(field, value);
+    return this;
+  }
+
+  repeatedString(field: number, values: string[]): ProtobufWriter {
+    values.forEach(value => this.delimited(field, utf8Encode(value)));
+    return this;
+  }
+
+  repeatedMessage(field: number, values: Uint8Array[]): ProtobufWriter {
+    values.forEach(value => this.delimited(field, value));
+    return this;
+  }
+
+  finish(): Uint8Array {
+    return new Uint8Array(this.buffer);
+  }
+}
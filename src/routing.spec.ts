@@ -0,0 +1,104 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { installMockCanopy, MockCanopy } from './testing';
+import { registerApp, SaplingContext } from './canopy';
+import { joinRoute, normalizeRoutePrefix, stripRoutePrefix } from './routing';
+
+describe('route helpers', () => {
+  it('should normalize route prefixes', () => {
+    expect(normalizeRoutePrefix('circuits/')).toEqual('/circuits');
+    expect(normalizeRoutePrefix('/')).toEqual('/');
+  });
+
+  it('should join and strip route prefixes', () => {
+    expect(joinRoute('/circuits', 'proposals/1')).toEqual(
+      '/circuits/proposals/1'
+    );
+    expect(joinRoute('/circuits', '/')).toEqual('/circuits');
+    expect(stripRoutePrefix('/circuits/proposals/1', '/circuits')).toEqual(
+      '/proposals/1'
+    );
+    expect(stripRoutePrefix('/circuits', '/circuits')).toEqual('/');
+  });
+});
+
+describe('sapling routing', () => {
+  let canopy: MockCanopy;
+  let mounted: string[];
+  let contexts: { [name: string]: SaplingContext };
+
+  function register(name: string, routePrefix?: string): void {
+    registerApp(
+      (domNode, context) => {
+        mounted.push(name);
+        contexts[name] = context;
+      },
+      { name, routePrefix }
+    );
+  }
+
+  beforeEach(() => {
+    mounted = [];
+    contexts = {};
+  });
+
+  afterEach(() => {
+    canopy.uninstall();
+  });
+
+  it('should mount the app matching a deep link', () => {
+    canopy = installMockCanopy({ initialPath: '/circuits/proposals/01234' });
+    register('circuits');
+    register('products', '/product-catalog');
+    canopy.mountApp(document.createElement('div'));
+    expect(mounted).toEqual(['circuits']);
+    expect(contexts.circuits.router.currentPath()).toEqual('/proposals/01234');
+  });
+
+  it('should navigate within the app and report route changes', () => {
+    const listener = jest.fn();
+    canopy = installMockCanopy();
+    register('circuits');
+    canopy.mountApp(document.createElement('div'));
+    const { router } = contexts.circuits;
+    router.onRouteChange(listener);
+    router.navigate('/proposals');
+    expect(canopy.history.location()).toEqual('/circuits/proposals');
+    expect(router.currentPath()).toEqual('/proposals');
+    expect(listener).toHaveBeenCalledWith('/proposals');
+  });
+
+  it('should link to and mount other saplings', () => {
+    canopy = installMockCanopy();
+    register('circuits');
+    register('products', '/product-catalog');
+    canopy.mountApp(document.createElement('div'), 'circuits');
+    const link = contexts.circuits.router.linkTo('products', 'items/7');
+    expect(link).toEqual('/product-catalog/items/7');
+    canopy.history.push(link);
+    expect(mounted).toEqual(['circuits', 'products']);
+    expect(canopy.mountedApp && canopy.mountedApp.name).toEqual('products');
+  });
+
+  it('should refuse to link to an unknown sapling', () => {
+    canopy = installMockCanopy();
+    register('circuits');
+    canopy.mountApp(document.createElement('div'));
+    expect(() => contexts.circuits.router.linkTo('missing')).toThrow(
+      'No app named missing'
+    );
+  });
+});
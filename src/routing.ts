@@ -0,0 +1,162 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { Emitter, Unsubscribe } from './events';
+
+/**
+ * The navigation history a host routes saplings with.
+ */
+export interface RouteHistory {
+  /** The current path, including any query string and fragment. */
+  location(): string;
+  /** Navigates to the given path. */
+  push(path: string): void;
+  /** Calls the listener with the new path after each navigation. */
+  listen(listener: (path: string) => void): Unsubscribe;
+}
+
+/**
+ * Lets an app read and change its route. Paths are relative to the app's
+ * route prefix, so `/proposals` in an app mounted at `/circuits` is
+ * `/circuits/proposals` in the browser.
+ */
+export interface SaplingRouter {
+  /** The route prefix the app is mounted at. */
+  routePrefix: string;
+  /** The current path within the app. */
+  currentPath(): string;
+  /** Navigates to a path within the app. */
+  navigate(path: string): void;
+  /**
+   * Calls the listener with the new path each time the route changes within
+   * the app.
+   */
+  onRouteChange(listener: (path: string) => void): Unsubscribe;
+  /**
+   * Returns the full path of a page of another sapling, for use in links.
+   * Throws if no sapling with that name is registered.
+   */
+  linkTo(saplingName: string, path?: string): string;
+}
+
+function withLeadingSlash(path: string): string {
+  return path.charAt(0) === '/' ? path : `/${path}`;
+}
+
+/**
+ * Normalizes a route prefix to start with a slash and not end with one.
+ */
+export function normalizeRoutePrefix(routePrefix: string): string {
+  const prefix = withLeadingSlash(routePrefix);
+  return prefix.length > 1 && prefix.charAt(prefix.length - 1) === '/'
+    ? prefix.slice(0, -1)
+    : prefix;
+}
+
+/**
+ * Returns true if the full path is the route prefix or a path below it.
+ */
+export function matchesRoutePrefix(path: string, routePrefix: string): boolean {
+  if (routePrefix === '/') {
+    return true;
+  }
+  if (path.indexOf(routePrefix) !== 0) {
+    return false;
+  }
+  const next = path.charAt(routePrefix.length);
+  return next === '' || next === '/' || next === '?' || next === '#';
+}
+
+/**
+ * Joins a route prefix and a path within it into a full path.
+ */
+export function joinRoute(routePrefix: string, path = '/'): string {
+  const relative = withLeadingSlash(path);
+  if (routePrefix === '/') {
+    return relative;
+  }
+  return relative === '/' ? routePrefix : `${routePrefix}${relative}`;
+}
+
+/**
+ * Strips the route prefix from a full path.
+ */
+export function stripRoutePrefix(path: string, routePrefix: string): string {
+  if (routePrefix === '/') {
+    return path;
+  }
+  return withLeadingSlash(path.slice(routePrefix.length));
+}
+
+/**
+ * Keeps the history in memory, for tests and hosts without a browser.
+ */
+export function memoryHistory(initialPath = '/'): RouteHistory {
+  let current = withLeadingSlash(initialPath);
+  const changes = new Emitter<string>();
+  return {
+    location: () => current,
+    push: path => {
+      current = withLeadingSlash(path);
+      changes.emit(current);
+    },
+    listen: listener => changes.subscribe(listener)
+  };
+}
+
+/**
+ * Routes through the browser's history, so that sapling routes appear in the
+ * address bar and follow the back and forward buttons.
+ */
+export function browserHistory(): RouteHistory {
+  const changes = new Emitter<string>();
+  const location = (): string => {
+    const { pathname, search, hash } = window.location;
+    return `${pathname}${search}${hash}`;
+  };
+  window.addEventListener('popstate', () => changes.emit(location()));
+  return {
+    location,
+    push: path => {
+      window.history.pushState(null, '', path);
+      changes.emit(location());
+    },
+    listen: listener => changes.subscribe(listener)
+  };
+}
+
+/**
+ * Builds the router of an app mounted at the given route prefix.
+ * @param resolveRoutePrefix - Returns the route prefix of a sapling by name.
+ */
+export function createRouter(
+  routePrefix: string,
+  history: RouteHistory,
+  resolveRoutePrefix: (saplingName: string) => string
+): SaplingRouter {
+  return {
+    routePrefix,
+    currentPath: () => stripRoutePrefix(history.location(), routePrefix),
+    navigate: path => history.push(joinRoute(routePrefix, path)),
+    onRouteChange: listener =>
+      history.listen(path => {
+        if (matchesRoutePrefix(path, routePrefix)) {
+          listener(stripRoutePrefix(path, routePrefix));
+        }
+      }),
+    linkTo: (saplingName, path) =>
+      joinRoute(resolveRoutePrefix(saplingName), path)
+  };
+}
@@ -0,0 +1,444 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  BatchInfo,
+  BatchInvalidError,
+  BatchTimeoutError,
+  submitBatchList,
+  waitForBatches
+} from './submitter';
+import {
+  SplinterAbortError,
+  SplinterClientError,
+  SplinterNetworkError,
+  SplinterParseError,
+  SplinterServerError,
+  SplinterSessionExpiredError
+} from './errors';
+import { Transport, TransportResponse } from './transport';
+import { installMockCanopy } from './testing';
+import { getNotifications } from './canopy';
+
+// Builds a transport that answers each request with the next queued response.
+function queuedTransport(responses: TransportResponse[]): jest.Mock {
+  return jest.fn(async () => responses.shift() as TransportResponse);
+}
+
+interface MockResponse {
+  status: number;
+  body: string;
+  networkError?: boolean;
+}
+
+interface MockRequest {
+  method: string;
+  url: string;
+  headers: { [name: string]: string };
+}
+
+interface MockXHR {
+  status: number;
+  response: string;
+  onload: () => void;
+  onerror: () => void;
+  open: jest.Mock;
+  setRequestHeader: jest.Mock;
+  getAllResponseHeaders: jest.Mock;
+  send: jest.Mock;
+}
+
+// Replaces window.XMLHttpRequest with a fake that answers each request with
+// the next queued response, and returns the list of requests made.
+function mockXHR(responses: MockResponse[]): MockRequest[] {
+  const requests: MockRequest[] = [];
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  (window as any).XMLHttpRequest = jest.fn(() => {
+    const xhr = {
+      status: 0,
+      response: ''
+    } as MockXHR;
+    const request = { headers: {} } as MockRequest;
+    xhr.open = jest.fn((method: string, url: string) => {
+      request.method = method;
+      request.url = url;
+      requests.push(request);
+    });
+    xhr.setRequestHeader = jest.fn((name: string, value: string) => {
+      request.headers[name] = value;
+    });
+    xhr.getAllResponseHeaders = jest.fn(() => '');
+    xhr.send = jest.fn(() => {
+      const next = responses.shift() as MockResponse;
+      xhr.status = next.status;
+      xhr.response = next.body;
+      setTimeout(() => (next.networkError ? xhr.onerror() : xhr.onload()));
+    });
+    return xhr;
+  });
+  return requests;
+}
+
+function batch(id: string, statusType: string): BatchInfo {
+  return {
+    id,
+    status: {
+      statusType,
+      message:
+        statusType === 'Invalid'
+          ? [{ transactionId: `${id}-txn`, errorMessage: 'bad', errorData: [] }]
+          : []
+    }
+  };
+}
+
+function ok(statuses: BatchInfo[]): MockResponse {
+  return { status: 200, body: JSON.stringify(statuses) };
+}
+
+describe('waitForBatches(url, ids, options)', () => {
+  it('should poll until every batch is committed', async () => {
+    const requests = mockXHR([
+      ok([batch('a', 'Pending'), batch('b', 'Committed')]),
+      ok([batch('a', 'Committed'), batch('b', 'Committed')])
+    ]);
+    const statuses = await waitForBatches('/batch_statuses', ['a', 'b'], {
+      interval: 1
+    });
+    expect(statuses.map(info => info.status.statusType)).toEqual([
+      'Committed',
+      'Committed'
+    ]);
+    expect(requests).toHaveLength(2);
+    expect(requests[0]).toMatchObject({
+      method: 'GET',
+      url: '/batch_statuses?ids=a,b'
+    });
+  });
+
+  it('should keep polling until every requested batch has a status', async () => {
+    const requests = mockXHR([
+      ok([]),
+      ok([batch('a', 'Committed')]),
+      ok([batch('a', 'Committed'), batch('b', 'Committed')])
+    ]);
+    const statuses = await waitForBatches('/batch_statuses', ['a', 'b'], {
+      interval: 1
+    });
+    expect(statuses.map(info => info.id)).toEqual(['a', 'b']);
+    expect(requests).toHaveLength(3);
+  });
+
+  it('should accept statuses wrapped in a data field', async () => {
+    mockXHR([
+      { status: 200, body: JSON.stringify({ data: [batch('a', 'Unknown')] }) }
+    ]);
+    const statuses = await waitForBatches('/batch_statuses?wait=5', ['a']);
+    expect(statuses).toEqual([batch('a', 'Unknown')]);
+  });
+
+  it('should reject with the invalid transactions', async () => {
+    expect.assertions(3);
+    mockXHR([ok([batch('a', 'Invalid'), batch('b', 'Committed')])]);
+    try {
+      await waitForBatches('/batch_statuses', ['a', 'b']);
+    } catch (err) {
+      expect(err).toBeInstanceOf(BatchInvalidError);
+      expect(err.statuses).toHaveLength(2);
+      expect(err.invalidTransactions).toEqual([
+        { transactionId: 'a-txn', errorMessage: 'bad', errorData: [] }
+      ]);
+    }
+  });
+
+  it('should reject when the timeout expires', async () => {
+    expect.assertions(1);
+    mockXHR([ok([batch('a', 'Pending')])]);
+    await expect(
+      waitForBatches('/batch_statuses', ['a'], { timeout: 5, interval: 10 })
+    ).rejects.toBeInstanceOf(BatchTimeoutError);
+  });
+
+  it('should not retry a status request past the timeout', async () => {
+    const transport = jest.fn().mockRejectedValue(
+      new SplinterNetworkError('down', {
+        method: 'GET',
+        url: '/batch_statuses?ids=a',
+        status: 0,
+        body: ''
+      })
+    );
+    await expect(
+      waitForBatches('/batch_statuses', ['a'], { transport, timeout: 100 })
+    ).rejects.toBeInstanceOf(SplinterNetworkError);
+    expect(transport).toHaveBeenCalledTimes(1);
+  });
+});
+
+describe('notifications', () => {
+  it('should notify the user once the batches are committed', async () => {
+    const canopy = installMockCanopy();
+    mockXHR([ok([batch('a', 'Committed'), batch('b', 'Committed')])]);
+    await waitForBatches('/batch_statuses', ['a', 'b'], { notify: true });
+    expect(getNotifications()).toMatchObject([
+      { level: 'success', title: 'Committed 2 batches', read: false }
+    ]);
+    canopy.uninstall();
+  });
+
+  it('should notify the user of invalid transactions', async () => {
+    const canopy = installMockCanopy();
+    mockXHR([ok([batch('a', 'Invalid'), batch('b', 'Committed')])]);
+    await expect(
+      waitForBatches('/batch_statuses', ['a', 'b'], { notify: true })
+    ).rejects.toBeInstanceOf(BatchInvalidError);
+    expect(getNotifications()).toMatchObject([
+      {
+        level: 'error',
+        title: 'Failed to commit 1 batch',
+        body: 'bad',
+        persistent: true
+      }
+    ]);
+    canopy.uninstall();
+  });
+
+  it('should notify the user when the submission fails', async () => {
+    const canopy = installMockCanopy();
+    mockXHR([{ status: 400, body: '{"message":"Invalid batch"}' }]);
+    await expect(
+      submitBatchList('/batches', new Uint8Array([1]), { notify: true })
+    ).rejects.toBeInstanceOf(SplinterClientError);
+    expect(getNotifications()).toMatchObject([
+      {
+        level: 'error',
+        title: 'Failed to submit the batches',
+        body: 'Invalid batch'
+      }
+    ]);
+    canopy.uninstall();
+  });
+
+  it('should not notify outside of a Canopy', async () => {
+    mockXHR([ok([batch('a', 'Committed')])]);
+    await expect(
+      waitForBatches('/batch_statuses', ['a'], { notify: true })
+    ).resolves.toHaveLength(1);
+  });
+});
+
+describe('submitBatchList(url, batchList)', () => {
+  const batchList = new Uint8Array([1, 2, 3]);
+
+  it('should resolve with the batch info from the response', async () => {
+    const requests = mockXHR([
+      { status: 202, body: JSON.stringify({ data: [batch('a', 'Pending')] }) }
+    ]);
+    await expect(submitBatchList('/batches', batchList)).resolves.toEqual([
+      batch('a', 'Pending')
+    ]);
+    expect(requests[0]).toEqual({
+      method: 'POST',
+      url: '/batches',
+      headers: { 'Content-Type': 'application/octet-stream' }
+    });
+  });
+
+  it('should reject with the Splinter message on a client error', async () => {
+    expect.assertions(6);
+    mockXHR([{ status: 400, body: '{"message":"Invalid batch"}' }]);
+    try {
+      await submitBatchList('/batches', batchList);
+    } catch (err) {
+      expect(err).toBeInstanceOf(SplinterClientError);
+      expect(err.message).toEqual('Invalid batch');
+      expect(err.status).toEqual(400);
+      expect(err.method).toEqual('POST');
+      expect(err.url).toEqual('/batches');
+      expect(err.data).toEqual({ message: 'Invalid batch' });
+    }
+  });
+
+  it('should reject with a server error on a 5xx response', async () => {
+    mockXHR([{ status: 500, body: 'internal error' }]);
+    await expect(
+      submitBatchList('/batches', batchList)
+    ).rejects.toBeInstanceOf(SplinterServerError);
+  });
+
+  it('should reject with a network error if the node is down', async () => {
+    mockXHR([{ status: 0, body: '', networkError: true }]);
+    await expect(
+      submitBatchList('/batches', batchList, { retry: false })
+    ).rejects.toBeInstanceOf(SplinterNetworkError);
+  });
+
+  it('should reject with a parse error on a malformed body', async () => {
+    mockXHR([{ status: 202, body: 'not json' }]);
+    await expect(
+      submitBatchList('/batches', batchList)
+    ).rejects.toBeInstanceOf(SplinterParseError);
+  });
+});
+
+describe('authenticated requests', () => {
+  const batchList = new Uint8Array([1, 2, 3]);
+
+  it('should send the token from the provider as a bearer token', async () => {
+    const requests = mockXHR([{ status: 202, body: '{"data":[]}' }]);
+    await submitBatchList('/batches', batchList, {
+      auth: async () => 'biome-token'
+    });
+    expect(requests[0].headers.Authorization).toEqual('Bearer biome-token');
+  });
+
+  it('should not send a token unless auth is requested', async () => {
+    const requests = mockXHR([{ status: 202, body: '{"data":[]}' }]);
+    await submitBatchList('/batches', batchList);
+    expect(requests[0].headers.Authorization).toBeUndefined();
+  });
+
+  it('should reject with a session expired error on a 401', async () => {
+    mockXHR([{ status: 401, body: '{"message":"Token expired"}' }]);
+    await expect(
+      submitBatchList('/batches', batchList, { auth: () => 'stale-token' })
+    ).rejects.toBeInstanceOf(SplinterSessionExpiredError);
+  });
+});
+
+describe('custom transports', () => {
+  it('should send the request through the given transport', async () => {
+    const transport = jest.fn(async () => ({
+      status: 202,
+      headers: {},
+      body: JSON.stringify({ data: [batch('a', 'Pending')] })
+    }));
+    const batchList = new Uint8Array([1, 2, 3]);
+    const statuses = await submitBatchList('http://node/batches', batchList, {
+      transport: transport as Transport
+    });
+    expect(statuses).toEqual([batch('a', 'Pending')]);
+    expect(transport).toHaveBeenCalledWith({
+      method: 'POST',
+      url: 'http://node/batches',
+      headers: { 'Content-Type': 'application/octet-stream' },
+      body: batchList
+    });
+  });
+});
+
+describe('retries and cancellation', () => {
+  const batchList = new Uint8Array([1, 2, 3]);
+  const accepted = { status: 202, headers: {}, body: '{"data":[]}' };
+
+  it('should retry a 503 after the Retry-After delay', async () => {
+    const transport = queuedTransport([
+      { status: 503, headers: { 'retry-after': '0' }, body: '' },
+      accepted
+    ]);
+    await expect(
+      submitBatchList('/batches', batchList, { transport })
+    ).resolves.toEqual([]);
+    expect(transport).toHaveBeenCalledTimes(2);
+  });
+
+  it('should retry network failures with backoff', async () => {
+    const transport = jest
+      .fn()
+      .mockRejectedValueOnce(
+        new SplinterNetworkError('down', {
+          method: 'POST',
+          url: '/batches',
+          status: 0,
+          body: ''
+        })
+      )
+      .mockResolvedValueOnce(accepted);
+    await submitBatchList('/batches', batchList, {
+      transport,
+      retry: { retries: 1, minDelay: 1 }
+    });
+    expect(transport).toHaveBeenCalledTimes(2);
+  });
+
+  it('should wait at most maxDelay for a Retry-After', async () => {
+    const transport = queuedTransport([
+      { status: 503, headers: { 'retry-after': '3600' }, body: '' },
+      accepted
+    ]);
+    await expect(
+      submitBatchList('/batches', batchList, {
+        transport,
+        retry: { retries: 1, maxDelay: 1 }
+      })
+    ).resolves.toEqual([]);
+    expect(transport).toHaveBeenCalledTimes(2);
+  });
+
+  it('should not retry past the timeout', async () => {
+    const transport = jest.fn().mockRejectedValue(
+      new SplinterNetworkError('down', {
+        method: 'POST',
+        url: '/batches',
+        status: 0,
+        body: ''
+      })
+    );
+    await expect(
+      submitBatchList('/batches', batchList, {
+        transport,
+        timeout: 50,
+        retry: { retries: 5, minDelay: 100, maxDelay: 100 }
+      })
+    ).rejects.toBeInstanceOf(SplinterNetworkError);
+    expect(transport).toHaveBeenCalledTimes(1);
+    expect(transport.mock.calls[0][0].timeout).toBeLessThanOrEqual(50);
+  });
+
+  it('should not retry client errors', async () => {
+    const transport = queuedTransport([
+      { status: 400, headers: {}, body: '' },
+      accepted
+    ]);
+    await expect(
+      submitBatchList('/batches', batchList, { transport })
+    ).rejects.toBeInstanceOf(SplinterClientError);
+    expect(transport).toHaveBeenCalledTimes(1);
+  });
+
+  it('should not retry when retries are disabled', async () => {
+    const transport = queuedTransport([
+      { status: 503, headers: { 'retry-after': '0' }, body: '' },
+      accepted
+    ]);
+    await expect(
+      submitBatchList('/batches', batchList, { transport, retry: false })
+    ).rejects.toBeInstanceOf(SplinterServerError);
+    expect(transport).toHaveBeenCalledTimes(1);
+  });
+
+  it('should reject without sending if the signal is already aborted', async () => {
+    const requests = mockXHR([{ status: 202, body: '{"data":[]}' }]);
+    const signal = ({
+      aborted: true,
+      addEventListener: jest.fn(),
+      removeEventListener: jest.fn()
+    } as unknown) as AbortSignal;
+    await expect(
+      submitBatchList('/batches', batchList, { signal })
+    ).rejects.toBeInstanceOf(SplinterAbortError);
+    expect(requests).toHaveLength(0);
+  });
+});
@@ -13,89 +13,265 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-interface BatchStatus {
-  statusType: string;
-  message: BatchMessage[];
-}
+/* eslint-disable max-classes-per-file */
+import { isInCanopy, notify as raiseNotification } from './canopy';
+import {
+  DEFAULT_RETRY_POLICY,
+  delay,
+  http,
+  parseJSON,
+  RequestOptions
+} from './http';
+import { TransportResponse } from './transport';
 
-interface BatchMessage {
+export interface BatchMessage {
   transactionId: string;
   errorMessage: string;
   errorData: number[];
 }
 
-interface BatchInfo {
+export interface BatchStatus {
+  statusType: string;
+  message: BatchMessage[];
+}
+
+export interface BatchInfo {
   id: string;
   status: BatchStatus;
 }
 
-const HTTPMethods = ['GET', 'POST', 'PUT', 'PATCH', 'DELETE'];
+export interface SubmitBatchListOptions extends RequestOptions {
+  /**
+   * Reports a failed submission as a Canopy notification. Ignored outside of
+   * a Canopy.
+   */
+  notify?: boolean;
+}
+
+export interface WaitForBatchesOptions extends RequestOptions {
+  /**
+   * Maximum time to wait for every batch to finish, in milliseconds. Each
+   * status request, retries included, is given the time remaining before
+   * this deadline.
+   */
+  timeout?: number;
+  /** Time between batch status requests, in milliseconds. */
+  interval?: number;
+  /**
+   * Reports whether the batches were committed as a Canopy notification.
+   * Ignored outside of a Canopy.
+   */
+  notify?: boolean;
+}
+
+const TERMINAL_STATUS_TYPES = ['Committed', 'Invalid', 'Unknown'];
+
+const DEFAULT_WAIT_TIMEOUT = 30000;
+const DEFAULT_WAIT_INTERVAL = 1000;
 
 /**
- * Wrapper function to set up XHR.
- * @param {string}      method    HTTP method for the request
- * @param {string}      url       endpoint to make the request to
- * @param {Uint8Array}  data      Byte array representation of the request body
- * @param {function}    headerFn  Function to set the correct request headers
+ * Raised by `waitForBatches` when at least one batch is invalid.
  */
-async function http(
-  method: string,
-  url: string,
-  data: Uint8Array | null,
-  headerFn: (request: XMLHttpRequest) => void
-): Promise<string> {
-  return new Promise((resolve, reject) => {
-    if (!HTTPMethods.includes(method.toUpperCase())) {
-      reject(Error('Invalid HTTP Method'));
-    }
+export class BatchInvalidError extends Error {
+  statuses: BatchInfo[];
 
-    const request = new XMLHttpRequest();
-    request.open(method, url);
-    if (headerFn) {
-      headerFn(request);
-    }
-    request.onload = (): void => {
-      if (request.status >= 200 && request.status < 300) {
-        resolve(request.response);
-      } else if (request.status >= 400 && request.status < 500) {
-        reject(
-          Error('Failed to send request. Contact the administrator for help.')
-        );
-      } else {
-        reject(
-          Error(
-            'The server has encountered an error. Please contact the administrator.'
-          )
-        );
-      }
-    };
-    request.onerror = (): void => {
-      reject(
-        Error(
-          'The server has encountered an error. Please contact the administrator.'
-        )
+  invalidTransactions: BatchMessage[];
+
+  constructor(statuses: BatchInfo[]) {
+    super('One or more batches were found to be invalid');
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'BatchInvalidError';
+    this.statuses = statuses;
+    this.invalidTransactions = statuses
+      .filter(info => info.status.statusType === 'Invalid')
+      .reduce(
+        (messages: BatchMessage[], info) =>
+          messages.concat(info.status.message),
+        []
       );
-    };
-    request.send(data);
-  });
+  }
 }
 
 /**
- * Submits a batch list of transaction batches
+ * Raised by `waitForBatches` when the batches have not all reached a terminal
+ * status before the timeout expires.
+ */
+export class BatchTimeoutError extends Error {
+  statuses: BatchInfo[];
+
+  constructor(statuses: BatchInfo[]) {
+    super('Timed out waiting for batches to be committed');
+    Object.setPrototypeOf(this, new.target.prototype);
+    this.name = 'BatchTimeoutError';
+    this.statuses = statuses;
+  }
+}
+
+/**
+ * Submits a batch list of transaction batches. Failed submissions are retried
+ * with `DEFAULT_RETRY_POLICY` unless another policy is given, which is safe as
+ * resubmitting a batch with the same ID does not apply it twice.
  * @param {string}      url       The endpoint to submit the batch list to
  * @param {Uint8Array}  batchList The serialized batch list
+ * @param {object}      options   Authentication, transport, timeout and retry
+ *                                options, and whether to notify the user if
+ *                                the submission fails
  */
 export async function submitBatchList(
   url: string,
-  batchList: Uint8Array
+  batchList: Uint8Array,
+  { notify = false, ...options }: SubmitBatchListOptions = {}
 ): Promise<BatchInfo[]> {
-  return http('POST', url, batchList, (request: XMLHttpRequest) => {
-    request.setRequestHeader('Content-Type', 'application/octet-stream');
-  })
-    .catch(err => {
-      throw new Error(err);
-    })
-    .then(body => {
-      return JSON.parse(body).data as BatchInfo[];
+  try {
+    const response = await http('POST', url, batchList, {
+      retry: DEFAULT_RETRY_POLICY,
+      ...options,
+      headers: { 'Content-Type': 'application/octet-stream' }
     });
+    return (parseJSON('POST', url, response) as { data: BatchInfo[] }).data;
+  } catch (err) {
+    if (notify && isInCanopy()) {
+      raiseNotification({
+        level: 'error',
+        title: 'Failed to submit the batches',
+        body: err.message,
+        persistent: true
+      });
+    }
+    throw err;
+  }
+}
+
+function parseBatchInfo(
+  url: string,
+  response: TransportResponse
+): BatchInfo[] {
+  const parsed = parseJSON('GET', url, response) as
+    | BatchInfo[]
+    | { data: BatchInfo[] };
+  // Scabbard returns a bare list of statuses, while older endpoints wrap the
+  // list in a `data` field.
+  return Array.isArray(parsed) ? parsed : parsed.data;
+}
+
+function plural(count: number, noun: string): string {
+  return count === 1 ? `1 ${noun}` : `${count} ${noun}es`;
+}
+
+// Raises a notification for the result of waiting for batches.
+function reportBatchResult(
+  batchIds: string[],
+  result: BatchInfo[] | Error
+): void {
+  const batches = plural(batchIds.length, 'batch');
+  if (!(result instanceof Error)) {
+    const unknown = result.filter(
+      info => info.status.statusType === 'Unknown'
+    ).length;
+    raiseNotification(
+      unknown === 0
+        ? { level: 'success', title: `Committed ${batches}` }
+        : {
+            level: 'warning',
+            title: `The node does not know of ${plural(unknown, 'batch')}`,
+            persistent: true
+          }
+    );
+  } else if (result instanceof BatchInvalidError) {
+    const invalid = result.statuses.filter(
+      info => info.status.statusType === 'Invalid'
+    ).length;
+    raiseNotification({
+      level: 'error',
+      title: `Failed to commit ${plural(invalid, 'batch')}`,
+      body: result.invalidTransactions
+        .map(({ errorMessage }) => errorMessage)
+        .join('\n'),
+      persistent: true
+    });
+  } else if (result instanceof BatchTimeoutError) {
+    raiseNotification({
+      level: 'warning',
+      title: `Timed out waiting for ${batches}`,
+      body: 'The batches may still be committed.',
+      persistent: true
+    });
+  } else {
+    raiseNotification({
+      level: 'error',
+      title: `Failed to check the status of ${batches}`,
+      body: result.message,
+      persistent: true
+    });
+  }
+}
+
+/**
+ * Polls the batch status endpoint until every batch has reached a terminal
+ * status (Committed, Invalid or Unknown).
+ * @param {string}    url       The batch_statuses endpoint to poll
+ * @param {string[]}  batchIds  The IDs of the batches to wait for
+ * @param {object}    options   Timeout and polling interval, in milliseconds,
+ *                              whether to notify the user of the result, and
+ *                              the options of each status request
+ */
+export async function waitForBatches(
+  url: string,
+  batchIds: string[],
+  {
+    timeout = DEFAULT_WAIT_TIMEOUT,
+    interval = DEFAULT_WAIT_INTERVAL,
+    notify = false,
+    ...options
+  }: WaitForBatchesOptions = {}
+): Promise<BatchInfo[]> {
+  const report = notify && isInCanopy();
+  const requestOptions = { retry: DEFAULT_RETRY_POLICY, ...options };
+  const separator = url.includes('?') ? '&' : '?';
+  const ids = batchIds.map(id => encodeURIComponent(id)).join(',');
+  const statusURL = `${url}${separator}ids=${ids}`;
+  const deadline = Date.now() + timeout;
+
+  const poll = async (): Promise<BatchInfo[]> => {
+    const statuses = parseBatchInfo(
+      statusURL,
+      await http('GET', statusURL, null, {
+        ...requestOptions,
+        timeout: Math.max(deadline - Date.now(), 1)
+      })
+    );
+    // Every requested batch must have a terminal status; the response may
+    // leave out batches the node has not seen yet.
+    const done = batchIds.every(id =>
+      statuses.some(
+        info =>
+          info.id === id &&
+          TERMINAL_STATUS_TYPES.includes(info.status.statusType)
+      )
+    );
+    if (done) {
+      return statuses;
+    }
+    if (Date.now() + interval > deadline) {
+      throw new BatchTimeoutError(statuses);
+    }
+    await delay(interval, options.signal, { method: 'GET', url: statusURL });
+    return poll();
+  };
+
+  try {
+    const statuses = await poll();
+    if (statuses.some(info => info.status.statusType === 'Invalid')) {
+      throw new BatchInvalidError(statuses);
+    }
+    if (report) {
+      reportBatchResult(batchIds, statuses);
+    }
+    return statuses;
+  } catch (err) {
+    if (report) {
+      reportBatchResult(batchIds, err);
+    }
+    throw err;
+  }
 }
@@ -0,0 +1,157 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { installMockCanopy, MockCanopy } from './testing';
+import {
+  getKeys,
+  getPublicKey,
+  getSharedConfig,
+  getUser,
+  hideCanopy,
+  isInCanopy,
+  onKeysChange,
+  onUserChange,
+  registerApp,
+  registerConfigSapling,
+  setKeys,
+  setKeySigner,
+  setUser,
+  sign
+} from './canopy';
+import { LOGIN_NAMESPACE } from './configNamespaces';
+import { KeysLockedError } from './errors';
+
+describe('installMockCanopy(options)', () => {
+  let canopy: MockCanopy;
+
+  afterEach(() => {
+    canopy.uninstall();
+  });
+
+  it('should install itself as window.$CANOPY', () => {
+    canopy = installMockCanopy();
+    expect(isInCanopy()).toBe(true);
+    expect(getSharedConfig().canopyConfig.splinterURL).toEqual(
+      'http://localhost:8080'
+    );
+    canopy.uninstall();
+    expect(isInCanopy()).toBe(false);
+  });
+
+  it('should round-trip the user and keys', () => {
+    const user = { userId: 'alice', token: 'alice.token' };
+    const keys = { publicKey: '02ab', privateKey: 'cd' };
+    canopy = installMockCanopy({ user: { userId: 'initial' } });
+    expect(getUser()).toEqual({ userId: 'initial' });
+    setUser(user);
+    setKeys(keys);
+    expect(getUser()).toEqual(user);
+    expect(getKeys()).toEqual(keys);
+  });
+
+  it('should record registered saplings and calls to hideCanopy', () => {
+    const login = (): void => {
+      /* no op */
+    };
+    canopy = installMockCanopy({
+      manifests: [
+        {
+          name: 'login',
+          configNamespaces: ['login'],
+          capabilities: ['user:write', 'keys:write']
+        }
+      ]
+    });
+    registerConfigSapling('login', login);
+    hideCanopy();
+    expect(canopy.configSaplings).toEqual([
+      {
+        configNamespace: LOGIN_NAMESPACE,
+        bootstrapFunction: login,
+        typed: false
+      }
+    ]);
+    expect(canopy.hideCanopyCount).toEqual(1);
+  });
+
+  it('should mount the registered app into a DOM node', () => {
+    canopy = installMockCanopy();
+    registerApp(domNode => {
+      (domNode as HTMLElement).innerHTML = '<h1>Sapling</h1>';
+    });
+    const domNode = canopy.mountApp(document.createElement('div'));
+    expect((domNode as HTMLElement).innerHTML).toEqual('<h1>Sapling</h1>');
+  });
+
+  it('should refuse to mount when no app is registered', () => {
+    canopy = installMockCanopy();
+    expect(() => canopy.mountApp(document.createElement('div'))).toThrow();
+  });
+
+  it('should notify subscribers of user changes until they unsubscribe', () => {
+    const listener = jest.fn();
+    canopy = installMockCanopy();
+    const unsubscribe = onUserChange(listener);
+    setUser({ userId: 'alice' });
+    setUser(null);
+    unsubscribe();
+    setUser({ userId: 'bob' });
+    expect(listener.mock.calls).toEqual([[{ userId: 'alice' }], [null]]);
+    expect(getUser()).toEqual({ userId: 'bob' });
+  });
+
+  it('should notify subscribers of key changes', () => {
+    const keys = { publicKey: '02ab', privateKey: 'cd' };
+    const listener = jest.fn();
+    canopy = installMockCanopy();
+    onKeysChange(listener);
+    setKeys(keys);
+    expect(listener).toHaveBeenCalledWith(keys);
+  });
+
+  it('should sign with the key signer until it is removed', async () => {
+    const message = new Uint8Array([1, 2, 3]);
+    const signer = {
+      getPublicKey: () => '02ab',
+      sign: jest.fn(async () => 'signature')
+    };
+    canopy = installMockCanopy({
+      keys: { publicKey: '02cd', privateKey: 'ef' }
+    });
+    setKeySigner(signer);
+    expect(getKeys()).toBeUndefined();
+    expect(getPublicKey()).toEqual('02ab');
+    await expect(sign(message)).resolves.toEqual('signature');
+    expect(signer.sign).toHaveBeenCalledWith(message);
+
+    setKeySigner(null);
+    expect(() => getPublicKey()).toThrow(KeysLockedError);
+    await expect(sign(message)).rejects.toBeInstanceOf(KeysLockedError);
+  });
+
+  it('should call the remaining subscribers when one throws', () => {
+    jest.useFakeTimers();
+    const listener = jest.fn();
+    canopy = installMockCanopy();
+    onUserChange(() => {
+      throw new Error('listener failed');
+    });
+    onUserChange(listener);
+    setUser({ userId: 'alice' });
+    expect(listener).toHaveBeenCalled();
+    expect(() => jest.runAllTimers()).toThrow('listener failed');
+    jest.useRealTimers();
+  });
+});
@@ -0,0 +1,107 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import { KeyPair, SharedConfig, User } from './canopy';
+import { ApprovedManifest, CanopyHost, createCanopyHost } from './host';
+import { memoryHistory } from './routing';
+
+export {
+  ConfigSaplingResult,
+  RegisteredApp,
+  RegisteredConfigSapling
+} from './host';
+
+export interface MockCanopyOptions {
+  /** The user returned by `getUser` until `setUser` is called. */
+  user?: User;
+  /** The keys returned by `getKeys` until `setKeys` is called. */
+  keys?: KeyPair;
+  /** Defaults to a Splinter node at `http://localhost:8080`. */
+  sharedConfig?: SharedConfig;
+  /** Path the in-memory history starts at. Defaults to `/`. */
+  initialPath?: string;
+  /**
+   * Manifests granting the capabilities saplings are registered with.
+   * Defaults to none.
+   */
+  manifests?: ApprovedManifest[];
+}
+
+/**
+ * A stateful fake Canopy, whose `windowCanopy` is installed as
+ * `window.$CANOPY`.
+ */
+export interface MockCanopy extends CanopyHost {
+  /**
+   * Unmounts the mounted app, calling its teardown, and removes the mock from
+   * `window.$CANOPY`, restoring whatever was there before it was installed.
+   */
+  uninstall(): void;
+}
+
+export const DEFAULT_MOCK_SHARED_CONFIG: SharedConfig = {
+  canopyConfig: {
+    splinterURL: 'http://localhost:8080'
+  }
+};
+
+/**
+ * Installs a fake Canopy as `window.$CANOPY` for sapling unit tests.
+ *
+ * The fake implements the whole `Canopy` interface: the user and keys set
+ * with `setUser` and `setKeys` are returned by `getUser` and `getKeys`,
+ * and registered saplings are recorded in `apps` and `configSaplings`.
+ *
+ * @example
+ * const canopy = installMockCanopy({ user: { userId: 'alice' } });
+ * registerApp(bootstrap);
+ * const domNode = canopy.mountApp(document.createElement('div'));
+ * canopy.uninstall();
+ */
+export function installMockCanopy(options: MockCanopyOptions = {}): MockCanopy {
+  const {
+    sharedConfig = DEFAULT_MOCK_SHARED_CONFIG,
+    user,
+    keys,
+    initialPath,
+    manifests
+  } = options;
+  // In order to prevent the need to overwrite the window interface,
+  // a intentional `any` is cast here.
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  const win = window as any;
+  const previous = win.$CANOPY;
+
+  const canopy = createCanopyHost({
+    sharedConfig,
+    user,
+    keys,
+    history: memoryHistory(initialPath),
+    manifests
+  }) as MockCanopy;
+  canopy.uninstall = () => {
+    canopy.unmountApp();
+    if (win.$CANOPY !== canopy.windowCanopy) {
+      return;
+    }
+    if (previous === undefined) {
+      delete win.$CANOPY;
+    } else {
+      win.$CANOPY = previous;
+    }
+  };
+  win.$CANOPY = canopy.windowCanopy;
+  return canopy;
+}
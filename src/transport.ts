@@ -0,0 +1,222 @@
+/**
+ * Copyright 2018-2020 Cargill Incorporated
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+import {
+  SplinterAbortError,
+  SplinterNetworkError,
+  SplinterTimeoutError
+} from './errors';
+
+export interface HttpHeaders {
+  [name: string]: string;
+}
+
+export interface TransportRequest {
+  method: string;
+  url: string;
+  headers: HttpHeaders;
+  body: Uint8Array | string | null;
+  /** Time to wait for a response, in milliseconds. */
+  timeout?: number;
+  signal?: AbortSignal;
+}
+
+export interface TransportResponse {
+  status: number;
+  /** Response headers, keyed by lower-case header name. */
+  headers: HttpHeaders;
+  body: string;
+}
+
+/**
+ * Sends a request and resolves with the response, whatever its status. A
+ * transport only rejects if no response was received: with a
+ * `SplinterTimeoutError` if the request timed out, a `SplinterAbortError` if it
+ * was aborted and a `SplinterNetworkError` otherwise.
+ */
+export interface Transport {
+  (request: TransportRequest): Promise<TransportResponse>;
+}
+
+type RequestTarget = Pick<TransportRequest, 'method' | 'url'>;
+
+export function networkError(
+  { method, url }: RequestTarget,
+  message = 'Unable to reach the server. Please contact the administrator.'
+): SplinterNetworkError {
+  return new SplinterNetworkError(message, {
+    method,
+    url,
+    status: 0,
+    body: ''
+  });
+}
+
+export function timeoutError({
+  method,
+  url,
+  timeout
+}: TransportRequest): SplinterTimeoutError {
+  return new SplinterTimeoutError(
+    `The server did not respond within ${timeout}ms`,
+    { method, url, status: 0, body: '' }
+  );
+}
+
+export function abortError({
+  method,
+  url
+}: RequestTarget): SplinterAbortError {
+  return new SplinterAbortError('The request was aborted', {
+    method,
+    url,
+    status: 0,
+    body: ''
+  });
+}
+
+function parseRawHeaders(rawHeaders: string): HttpHeaders {
+  return rawHeaders
+    .trim()
+    .split(/[\r\n]+/)
+    .reduce((headers: HttpHeaders, line) => {
+      const separator = line.indexOf(':');
+      if (separator > 0) {
+        const name = line.slice(0, separator).trim().toLowerCase();
+        return { ...headers, [name]: line.slice(separator + 1).trim() };
+      }
+      return headers;
+    }, {});
+}
+
+/**
+ * Transport backed by `XMLHttpRequest`.
+ */
+export const xhrTransport: Transport = request =>
+  new Promise((resolve, reject) => {
+    const { signal } = request;
+    if (signal && signal.aborted) {
+      reject(abortError(request));
+      return;
+    }
+
+    const xhr = new XMLHttpRequest();
+    const onAbort = (): void => xhr.abort();
+    const settle = (): void => {
+      if (signal) {
+        signal.removeEventListener('abort', onAbort);
+      }
+    };
+
+    xhr.open(request.method, request.url);
+    Object.keys(request.headers).forEach(name => {
+      xhr.setRequestHeader(name, request.headers[name]);
+    });
+    if (request.timeout) {
+      xhr.timeout = request.timeout;
+    }
+    xhr.onload = (): void => {
+      settle();
+      resolve({
+        status: xhr.status,
+        headers: parseRawHeaders(xhr.getAllResponseHeaders() || ''),
+        body: xhr.response || ''
+      });
+    };
+    xhr.onerror = (): void => {
+      settle();
+      reject(networkError(request));
+    };
+    xhr.ontimeout = (): void => {
+      settle();
+      reject(timeoutError(request));
+    };
+    xhr.onabort = (): void => {
+      settle();
+      reject(abortError(request));
+    };
+    if (signal) {
+      signal.addEventListener('abort', onAbort);
+    }
+    xhr.send(request.body);
+  });
+
+/**
+ * Transport backed by the Fetch API.
+ */
+export const fetchTransport: Transport = async request => {
+  const { signal, timeout } = request;
+  if (signal && signal.aborted) {
+    throw abortError(request);
+  }
+
+  // fetch has no timeout of its own, so the request is aborted through a
+  // controller that is also triggered by the caller's signal.
+  const controller = new AbortController();
+  const onAbort = (): void => controller.abort();
+  let timedOut = false;
+  const timer = timeout
+    ? setTimeout(() => {
+        timedOut = true;
+        controller.abort();
+      }, timeout)
+    : undefined;
+  if (signal) {
+    signal.addEventListener('abort', onAbort);
+  }
+
+  try {
+    const response = await fetch(request.url, {
+      method: request.method,
+      headers: request.headers,
+      body: request.body === null ? undefined : request.body,
+      signal: controller.signal
+    });
+    const headers: HttpHeaders = {};
+    response.headers.forEach((value, name) => {
+      headers[name.toLowerCase()] = value;
+    });
+    return { status: response.status, headers, body: await response.text() };
+  } catch (err) {
+    if (timedOut) {
+      throw timeoutError(request);
+    }
+    if (controller.signal.aborted) {
+      throw abortError(request);
+    }
+    throw networkError(request, err.message);
+  } finally {
+    clearTimeout(timer);
+    if (signal) {
+      signal.removeEventListener('abort', onAbort);
+    }
+  }
+};
+
+/**
+ * Returns the transport available in the current environment, preferring
+ * `XMLHttpRequest` and falling back to the Fetch API.
+ */
+export function defaultTransport(): Transport {
+  if (typeof XMLHttpRequest !== 'undefined') {
+    return xhrTransport;
+  }
+  if (typeof fetch !== 'undefined') {
+    return fetchTransport;
+  }
+  throw new Error(
+    'No HTTP transport is available in this environment; pass a transport explicitly'
+  );
+}